@@ -8,6 +8,11 @@ fn main() {
         .nth(1)
         .and_then(|value| value.parse::<u16>().ok())
         .unwrap_or(0);
+    let startup_delay_ms = env::args()
+        .nth(2)
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(0);
+    thread::sleep(Duration::from_millis(startup_delay_ms));
     let addr = ("127.0.0.1", port);
     let listener = TcpListener::bind(addr).expect("bind failed");
     listener
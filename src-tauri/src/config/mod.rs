@@ -1,80 +1,75 @@
-use crate::models::{HealthCheck, PortDef, RestartPolicy, ServiceDefinition};
-use crate::runtime;
-use std::collections::HashMap;
+use crate::drivers;
+use crate::models::ServiceDefinition;
+use std::fs;
+use std::path::Path;
 
 pub mod php;
 
-fn default_health_check(_service_id: &str, port: u16) -> HealthCheck {
-    HealthCheck {
-        kind: "port".to_string(),
-        target: format!("127.0.0.1:{}", port),
-        timeout_ms: 3000,
-        interval_ms: 2000,
-    }
-}
-
-fn default_restart_policy() -> RestartPolicy {
-    RestartPolicy {
-        max_retries: 3,
-        backoff_ms: 2000,
-    }
+// One definition per built-in driver; see drivers/ for what each one runs.
+pub fn default_services() -> Vec<ServiceDefinition> {
+    drivers::all()
+        .iter()
+        .map(|driver| drivers::definition(driver.as_ref()))
+        .collect()
 }
 
-fn bin_path(service: &str) -> String {
-    let versions = runtime::default_versions();
-    let version = versions.get(service).cloned().unwrap_or_else(|| "0.0.0".to_string());
-    runtime::bin_path_for(service, &version)
-}
+// Built-ins followed by the definitions in {root}/app/config/services.d/*.json.
+// Files that fail validation are skipped and reported as errors.
+pub fn load_services(root: &Path) -> (Vec<ServiceDefinition>, Vec<String>) {
+    let mut services = default_services();
+    let mut errors = Vec::new();
+    let dir = root.join("app/config/services.d");
+    let mut paths: Vec<_> = match fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
 
-fn service_def(id: &str, name: &str, port: u16) -> ServiceDefinition {
-    ServiceDefinition {
-        id: id.to_string(),
-        name: name.to_string(),
-        binary: bin_path(id),
-        args: Vec::new(),
-        env: HashMap::new(),
-        cwd: "runtime".to_string(),
-        ports: vec![PortDef {
-            name: "main".to_string(),
-            port,
-            protocol: "tcp".to_string(),
-        }],
-        depends_on: Vec::new(),
-        health_check: default_health_check(id, port),
-        restart_policy: default_restart_policy(),
+    let mut custom = Vec::new();
+    for path in paths {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        match load_definition(&path) {
+            Ok(def) if services.iter().chain(custom.iter()).any(|d: &ServiceDefinition| d.id == def.id) => {
+                errors.push(format!("{name}: duplicate service id {}", def.id));
+            }
+            Ok(def) => custom.push(def),
+            Err(err) => errors.push(format!("{name}: {err}")),
+        }
     }
-}
 
-pub fn default_services() -> Vec<ServiceDefinition> {
-    let mut services = vec![
-        service_def("php", "PHP", 9000),
-        service_def("node", "Node.js", 3000),
-        service_def("postgres", "Postgres", 5432),
-        service_def("mariadb", "MariaDB", 3306),
-        service_def("mailpit", "Mailpit", 8025),
-    ];
-    for service in &mut services {
-        if service.id == "postgres" {
-            service.env.insert("PGDATA".to_string(), "runtime/data/postgres".to_string());
-            service.args = vec!["-D".to_string(), "runtime/data/postgres".to_string()];
-        }
-        if service.id == "mariadb" {
-            service.args = vec![
-                format!("--defaults-file=runtime/config/mariadb/my.cnf"),
-                "--datadir=runtime/data/mariadb".to_string(),
-            ];
-        }
-        if service.id == "php" {
-            service.env.insert("PHP_INI_SCAN_DIR".to_string(), "runtime/config/php".to_string());
-            // Use built-in server for immediate "It works" experience without Nginx
-            service.args = vec![
-                "-S".to_string(), "0.0.0.0:8000".to_string(),
-                "-t".to_string(), "runtime/www".to_string()
-            ];
-        }
-        if service.id == "php" || service.id == "node" {
-            service.depends_on = vec!["postgres".to_string(), "mariadb".to_string()];
+    // Drop definitions whose dependencies are unknown, repeating because a
+    // dropped service may be the dependency of another custom one.
+    loop {
+        let known: Vec<String> = services
+            .iter()
+            .chain(custom.iter())
+            .map(|def| def.id.clone())
+            .collect();
+        let before = custom.len();
+        custom.retain(|def| match def.depends_on.iter().find(|dep| !known.contains(dep)) {
+            Some(dep) => {
+                errors.push(format!("{}: unknown dependency {dep}", def.id));
+                false
+            }
+            None => true,
+        });
+        if custom.len() == before {
+            break;
         }
     }
-    services
+    services.extend(custom);
+    (services, errors)
+}
+
+fn load_definition(path: &Path) -> Result<ServiceDefinition, String> {
+    let raw = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let value: serde_json::Value = serde_json::from_str(&raw).map_err(|e| e.to_string())?;
+    crate::schema::validate_service_definition(&value)?;
+    let def: ServiceDefinition = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    crate::template::validate_definition(&def)?;
+    Ok(def)
 }
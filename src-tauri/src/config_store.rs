@@ -20,6 +20,14 @@ pub struct AppConfig {
     pub update_feed_url: String,
     #[serde(rename = "updatePublicKeys")]
     pub update_public_keys: Vec<String>,
+    // Total size allowed for app/logs before the janitor deletes old rotations.
+    #[serde(rename = "logDiskBudgetMb")]
+    #[serde(default = "default_log_disk_budget_mb")]
+    pub log_disk_budget_mb: u64,
+    // Name of the last profile activated; cleared when that profile is deleted.
+    #[serde(rename = "activeProfile")]
+    #[serde(default)]
+    pub active_profile: Option<String>,
 }
 
 impl Default for AppConfig {
@@ -31,6 +39,8 @@ impl Default for AppConfig {
             telemetry_opt_in: false,
             update_feed_url: "https://updates.kojibox.dev/feed.json".to_string(),
             update_public_keys: Vec::new(),
+            log_disk_budget_mb: default_log_disk_budget_mb(),
+            active_profile: None,
         }
     }
 }
@@ -82,6 +92,93 @@ pub struct ServiceConfig {
     pub ports: HashMap<String, u16>,
     pub env: HashMap<String, String>,
     pub args: Vec<String>,
+    #[serde(rename = "logRotation")]
+    #[serde(default)]
+    pub log_rotation: LogRotation,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LogRotation {
+    #[serde(rename = "maxSizeBytes")]
+    #[serde(default = "default_log_max_size")]
+    pub max_size_bytes: u64,
+    #[serde(rename = "maxFiles")]
+    #[serde(default = "default_log_max_files")]
+    pub max_files: u32,
+    // Rotated files older than this are deleted; 0 keeps them regardless of age.
+    #[serde(rename = "maxAgeDays")]
+    #[serde(default = "default_log_max_age_days")]
+    pub max_age_days: u32,
+    #[serde(default = "default_log_compress")]
+    pub compress: bool,
+}
+
+impl Default for LogRotation {
+    fn default() -> Self {
+        Self {
+            max_size_bytes: default_log_max_size(),
+            max_files: default_log_max_files(),
+            max_age_days: default_log_max_age_days(),
+            compress: default_log_compress(),
+        }
+    }
+}
+
+// A named stack, e.g. "laravel" = php + mariadb + mailpit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(rename = "schemaVersion")]
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
+    pub name: String,
+    pub services: Vec<ProfileService>,
+}
+
+// Overrides are layered over the service's own ServiceConfig while the
+// profile is being activated; they are never written back to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileService {
+    pub id: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub ports: HashMap<String, u16>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+}
+
+impl ProfileService {
+    pub fn apply(&self, config: &mut ServiceConfig) {
+        if let Some(version) = &self.version {
+            config.version = Some(version.clone());
+        }
+        for (name, port) in &self.ports {
+            config.ports.insert(name.clone(), *port);
+        }
+        for (key, value) in &self.env {
+            config.env.insert(key.clone(), value.clone());
+        }
+    }
+}
+
+fn default_log_max_size() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_log_max_files() -> u32 {
+    3
+}
+
+fn default_log_max_age_days() -> u32 {
+    14
+}
+
+fn default_log_compress() -> bool {
+    true
+}
+
+fn default_log_disk_budget_mb() -> u64 {
+    512
 }
 
 impl Default for ServiceConfig {
@@ -94,6 +191,7 @@ impl Default for ServiceConfig {
             ports: HashMap::new(),
             env: HashMap::new(),
             args: Vec::new(),
+            log_rotation: LogRotation::default(),
         }
     }
 }
@@ -239,6 +337,76 @@ impl ConfigStore {
         Ok(config)
     }
 
+    pub fn list_profiles(&self) -> Result<Vec<Profile>, String> {
+        let dir = self.profiles_dir();
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut names: Vec<String> = fs::read_dir(&dir)
+            .map_err(|e| e.to_string())?
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                    return None;
+                }
+                path.file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .map(str::to_string)
+            })
+            .collect();
+        names.sort();
+        names.iter().map(|name| self.load_profile(name)).collect()
+    }
+
+    pub fn load_profile(&self, name: &str) -> Result<Profile, String> {
+        let path = self.profile_path(name);
+        if !path.exists() {
+            return Err(format!("profile not found: {name}"));
+        }
+        let raw = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        let value: Value = serde_json::from_str(&raw).map_err(|e| e.to_string())?;
+        crate::schema::validate_profile(&value)?;
+        let profile: Profile = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        if profile.name != name {
+            return Err("profile name mismatch".to_string());
+        }
+        Ok(profile)
+    }
+
+    pub fn save_profile(&self, profile: &Profile) -> Result<(), String> {
+        let mut profile = profile.clone();
+        profile.schema_version = 1;
+        let value = serde_json::to_value(&profile).map_err(|e| e.to_string())?;
+        crate::schema::validate_profile(&value)?;
+        let path = self.profile_path(&profile.name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let raw = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+        fs::write(path, raw).map_err(|e| e.to_string())
+    }
+
+    pub fn delete_profile(&self, name: &str) -> Result<(), String> {
+        let path = self.profile_path(name);
+        if !path.exists() {
+            return Err(format!("profile not found: {name}"));
+        }
+        fs::remove_file(&path).map_err(|e| e.to_string())?;
+        let mut app = self.load_app_config_or_default();
+        if app.active_profile.as_deref() == Some(name) {
+            app.active_profile = None;
+            self.save_app_config(&app)?;
+        }
+        Ok(())
+    }
+
+    pub fn set_active_profile(&self, name: Option<&str>) -> Result<(), String> {
+        let mut app = self.load_app_config_or_default();
+        app.active_profile = name.map(str::to_string);
+        self.save_app_config(&app)
+    }
+
     fn app_config_path(&self) -> PathBuf {
         self.root.join("app/config/app.json")
     }
@@ -247,6 +415,14 @@ impl ConfigStore {
         self.root.join("app/config/ports.json")
     }
 
+    fn profiles_dir(&self) -> PathBuf {
+        self.root.join("app/config/profiles")
+    }
+
+    fn profile_path(&self, name: &str) -> PathBuf {
+        self.profiles_dir().join(format!("{name}.json"))
+    }
+
     fn service_config_path(&self, id: &str) -> PathBuf {
         self.root.join(format!("app/config/services/{id}.json"))
     }
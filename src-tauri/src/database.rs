@@ -1,70 +1,34 @@
+use std::fs;
 use std::path::PathBuf;
-use std::process::Command;
-use crate::runtime::RuntimeManager;
+use crate::drivers::{self, DriverContext};
 
-#[derive(Debug)]
-pub struct DatabaseManager {
-    runtime: RuntimeManager,
-}
+// Dumps and restores go through the service's driver, against the running
+// instance described by the context (rendered ports, data dir, tools).
+#[derive(Debug, Default)]
+pub struct DatabaseManager;
 
 impl DatabaseManager {
-    pub fn new(runtime: RuntimeManager) -> Self {
-        Self { runtime }
+    pub fn new() -> Self {
+        Self
     }
 
-    pub fn dump(&self, service: &str, db_name: &str, output: PathBuf) -> Result<String, String> {
-        let (bin, args) = match service {
-            "postgres" => {
-                let bin = self.runtime.resolve_binary("postgres")
-                    .map_err(|_| "postgres binary not found".to_string())?;
-                // Assume pg_dump is in same dir
-                let pg_dump = bin.parent().unwrap().join(if cfg!(target_os="windows") { "pg_dump.exe" } else { "pg_dump" });
-                if !pg_dump.exists() {
-                    return Err("pg_dump not found".to_string());
-                }
-                // Port needs to be looked up from config/running service, but for now assume default or 5432
-                // Ideally we get port from ServiceManager. 
-                // For simplicity MVP, we assume local 5432 or 3306 based on service id.
-                // Or better, passing connection string?
-                // Let's use default ports for now as we don't have easy access to ConfigStore here without passing it.
-                (pg_dump, vec![
-                    "-h".to_string(), "127.0.0.1".to_string(),
-                    "-p".to_string(), "5432".to_string(),
-                    "-U".to_string(), "postgres".to_string(),
-                    "-f".to_string(), output.to_string_lossy().to_string(),
-                    db_name.to_string()
-                ])
-            },
-            "mariadb" => {
-                let bin = self.runtime.resolve_binary("mariadb")
-                    .map_err(|_| "mariadb binary not found".to_string())?;
-                let mysqldump = bin.parent().unwrap().join(if cfg!(target_os="windows") { "mysqldump.exe" } else { "mysqldump" });
-                if !mysqldump.exists() {
-                    return Err("mysqldump not found".to_string());
-                }
-                (mysqldump, vec![
-                    "-h".to_string(), "127.0.0.1".to_string(),
-                    "-P".to_string(), "3306".to_string(),
-                    "-u".to_string(), "root".to_string(),
-                    // Pass empty password? mysql usually needs -p with empty if socket auth not used
-                    // output redirection > is shell feature. Command::output doesn't do >.
-                    // mysqldump -r output_file
-                    "--result-file".to_string(), output.to_string_lossy().to_string(),
-                    db_name.to_string()
-                ])
-            },
-            _ => return Err("unsupported service for dump".to_string()),
-        };
-
-        let output = Command::new(&bin)
-            .args(&args)
-            .output()
-            .map_err(|e| e.to_string())?;
-
-        if !output.status.success() {
-            return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    pub fn dump(&self, context: &DriverContext, db_name: &str, output: PathBuf) -> Result<String, String> {
+        let driver = drivers::find(&context.def.id)
+            .ok_or_else(|| "unsupported service for dump".to_string())?;
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
         }
-
+        driver.dump(context, db_name, &output)?;
         Ok("Dump successful".to_string())
     }
+
+    pub fn restore(&self, context: &DriverContext, db_name: &str, input: PathBuf) -> Result<String, String> {
+        let driver = drivers::find(&context.def.id)
+            .ok_or_else(|| "unsupported service for restore".to_string())?;
+        if !input.exists() {
+            return Err(format!("dump not found: {}", input.display()));
+        }
+        driver.restore(context, db_name, &input)?;
+        Ok("Restore successful".to_string())
+    }
 }
@@ -1,5 +1,6 @@
 use crate::config_store::AppConfig;
-use crate::models::{LogEntry, ServiceState};
+use crate::health_history::HealthReport;
+use crate::models::{LaunchPlan, LogEntry, ServiceState};
 use serde::Serialize;
 use std::collections::HashMap;
 use std::fs;
@@ -20,6 +21,20 @@ pub struct DiagnosticsBundle {
     pub log_files: Vec<String>,
     #[serde(rename = "serviceConfigs")]
     pub service_configs: Vec<serde_json::Value>,
+    #[serde(flatten)]
+    pub reports: ServiceReports,
+}
+
+// What the service manager can tell about each service beyond its state.
+#[derive(Debug, Default, Serialize)]
+pub struct ServiceReports {
+    // What each service would run if started now, secrets masked.
+    #[serde(rename = "launchPlans")]
+    pub launch_plans: Vec<LaunchPlan>,
+    #[serde(rename = "launchPlanErrors")]
+    pub launch_plan_errors: Vec<String>,
+    #[serde(rename = "healthHistory")]
+    pub health_history: Vec<HealthReport>,
 }
 
 pub fn write_bundle(
@@ -28,6 +43,7 @@ pub fn write_bundle(
     services: Vec<ServiceState>,
     logs: HashMap<String, Vec<LogEntry>>,
     service_configs: Vec<serde_json::Value>,
+    reports: ServiceReports,
 ) -> Result<String, String> {
     let root = root.into();
     let cache_dir = root.join("app/cache");
@@ -42,6 +58,7 @@ pub fn write_bundle(
         logs,
         log_files,
         service_configs,
+        reports,
     };
     let raw = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
     let file = fs::File::create(&path).map_err(|e| e.to_string())?;
@@ -0,0 +1,75 @@
+use super::{exe_name, strings, ServiceDriver};
+use crate::models::{HealthCheck, HttpProbe};
+use crate::runtime::{archive_extension, ArchiveKind, OfficialSource};
+
+pub struct MailpitDriver;
+
+impl ServiceDriver for MailpitDriver {
+    fn id(&self) -> &'static str {
+        "mailpit"
+    }
+
+    fn name(&self) -> &'static str {
+        "Mailpit"
+    }
+
+    fn default_version(&self) -> &'static str {
+        "1.15.0"
+    }
+
+    fn default_port(&self) -> u16 {
+        8025
+    }
+
+    fn args(&self) -> Vec<String> {
+        strings(&["--listen=0.0.0.0:${port.main}"])
+    }
+
+    fn log_format(&self) -> Option<&'static str> {
+        Some("mailpit")
+    }
+
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck {
+            kind: "http".to_string(),
+            target: "http://127.0.0.1:${port.main}/livez".to_string(),
+            timeout_ms: 3000,
+            interval_ms: 2000,
+            http: Some(HttpProbe::default()),
+            command: Vec::new(),
+        }
+    }
+
+    fn config_templates(&self) -> Vec<(&'static str, &'static str)> {
+        vec![("default.conf", "# mailpit default config\n")]
+    }
+
+    fn official_source(&self, version: &str, os: &str, arch: &str) -> Option<OfficialSource> {
+        let platform = match os {
+            "windows" => "windows",
+            "macos" => "darwin",
+            "linux" => "linux",
+            _ => return None,
+        };
+        let arch = match arch {
+            "x64" => "amd64",
+            "arm64" => "arm64",
+            _ => return None,
+        };
+        let archive = if os == "windows" {
+            ArchiveKind::Zip
+        } else {
+            ArchiveKind::TarGz
+        };
+        let ext = archive_extension(archive);
+        let target_binary = exe_name("mailpit", os);
+        Some(OfficialSource {
+            url: format!(
+                "https://github.com/axllent/mailpit/releases/download/v{version}/mailpit-{platform}-{arch}.{ext}"
+            ),
+            archive,
+            binary_names: vec![target_binary.clone()],
+            target_binary,
+        })
+    }
+}
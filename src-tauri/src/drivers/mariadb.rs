@@ -0,0 +1,142 @@
+use super::{exe_name, strings, DriverContext, ServiceDriver};
+use crate::models::{HealthCheck, StopPolicy};
+use crate::runtime::{archive_extension, ArchiveKind, OfficialSource};
+use std::fs;
+use std::path::Path;
+
+pub struct MariadbDriver;
+
+impl MariadbDriver {
+    fn connection_args(ctx: &DriverContext) -> Result<Vec<String>, String> {
+        let port = ctx.port("main")?;
+        Ok(vec![
+            "--host=127.0.0.1".to_string(),
+            format!("--port={port}"),
+            "--user=root".to_string(),
+        ])
+    }
+}
+
+impl ServiceDriver for MariadbDriver {
+    fn id(&self) -> &'static str {
+        "mariadb"
+    }
+
+    fn name(&self) -> &'static str {
+        "MariaDB"
+    }
+
+    fn default_version(&self) -> &'static str {
+        "10.11.6"
+    }
+
+    fn default_port(&self) -> u16 {
+        3306
+    }
+
+    fn args(&self) -> Vec<String> {
+        strings(&[
+            "--defaults-file=${runtime}/config/mariadb/my.cnf",
+            "--datadir=${data_dir}",
+            "--port=${port.main}",
+        ])
+    }
+
+    fn log_format(&self) -> Option<&'static str> {
+        Some("mariadb")
+    }
+
+    // mariadb listens before crash recovery has finished; ping waits for it.
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck {
+            kind: "exec".to_string(),
+            target: String::new(),
+            command: strings(&[
+                "mariadb-admin",
+                "--host=127.0.0.1",
+                "--port=${port.main}",
+                "--user=root",
+                "ping",
+            ]),
+            ..HealthCheck::default()
+        }
+    }
+
+    fn stop_policy(&self) -> StopPolicy {
+        StopPolicy {
+            signal: "term".to_string(),
+            command: strings(&[
+                "mariadb-admin",
+                "--host=127.0.0.1",
+                "--port=${port.main}",
+                "--user=root",
+                "shutdown",
+            ]),
+            timeout_ms: 30_000,
+        }
+    }
+
+    fn config_templates(&self) -> Vec<(&'static str, &'static str)> {
+        vec![(
+            "my.cnf.tmpl",
+            "[mysqld]\nport=${port.main}\ndatadir=${data_dir}\nlog_error=${log_dir}/mariadb.log\n",
+        )]
+    }
+
+    fn init_data(&self, ctx: &DriverContext) -> Result<(), String> {
+        fs::create_dir_all(&ctx.data_dir).map_err(|e| e.to_string())?;
+        if ctx.data_dir.join("mysql").exists() {
+            return Ok(());
+        }
+        ctx.run_tool(
+            "mariadb-install-db",
+            &[format!("--datadir={}", ctx.data_dir.display())],
+        )
+    }
+
+    fn dump(&self, ctx: &DriverContext, database: &str, output: &Path) -> Result<(), String> {
+        let mut args = Self::connection_args(ctx)?;
+        args.extend([
+            format!("--result-file={}", output.display()),
+            database.to_string(),
+        ]);
+        ctx.run_tool("mysqldump", &args)
+    }
+
+    // The client's `source` command reads the file itself, so no shell
+    // redirection is needed.
+    fn restore(&self, ctx: &DriverContext, database: &str, input: &Path) -> Result<(), String> {
+        let mut args = Self::connection_args(ctx)?;
+        args.extend([
+            format!("--database={database}"),
+            format!("--execute=source {}", input.display()),
+        ]);
+        ctx.run_tool("mysql", &args)
+    }
+
+    fn official_source(&self, version: &str, os: &str, arch: &str) -> Option<OfficialSource> {
+        let (platform, archive) = match os {
+            "windows" => ("winx64", ArchiveKind::Zip),
+            "linux" => ("linux-x86_64", ArchiveKind::TarGz),
+            "macos" => ("macosx", ArchiveKind::TarGz),
+            _ => return None,
+        };
+        if arch != "x64" {
+            return None;
+        }
+        let ext = archive_extension(archive);
+        let target_binary = exe_name("mariadb", os);
+        Some(OfficialSource {
+            url: format!(
+                "https://archive.mariadb.org/mariadb-{version}/{platform}/mariadb-{version}-{platform}.{ext}"
+            ),
+            archive,
+            binary_names: vec![
+                target_binary.clone(),
+                exe_name("mariadbd", os),
+                exe_name("mysqld", os),
+            ],
+            target_binary,
+        })
+    }
+}
@@ -0,0 +1,238 @@
+use crate::models::{
+    HealthCheck, LifecycleHooks, PortDef, ReloadPolicy, RestartPolicy, ServiceDefinition,
+    StopPolicy,
+};
+use crate::runtime::{self, OfficialSource, ServiceBinary};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+mod mailpit;
+mod mariadb;
+mod node;
+mod php;
+mod postgres;
+
+// Everything that is specific to one built-in service. The service manager,
+// installer, runtime downloader and database tools only talk to drivers, so
+// adding a service means implementing this trait and listing it in `all`.
+//
+// Strings returned here may use the ${...} placeholders from template.rs;
+// they are resolved when the service is spawned.
+pub trait ServiceDriver: Send + Sync {
+    fn id(&self) -> &'static str;
+    fn name(&self) -> &'static str;
+    // Version installed and listed in the runtime manifest by default.
+    fn default_version(&self) -> &'static str;
+    fn default_port(&self) -> u16;
+
+    fn args(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn env(&self) -> HashMap<String, String> {
+        HashMap::new()
+    }
+
+    fn depends_on(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    // Parser for captured output, see log_parser.rs.
+    fn log_format(&self) -> Option<&'static str> {
+        None
+    }
+
+    // By default a service is ready once its main port accepts connections.
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck {
+            kind: "port".to_string(),
+            target: "127.0.0.1:${port.main}".to_string(),
+            timeout_ms: 3000,
+            interval_ms: 2000,
+            http: None,
+            command: Vec::new(),
+        }
+    }
+
+    fn stop_policy(&self) -> StopPolicy {
+        StopPolicy::default()
+    }
+
+    fn reload_policy(&self) -> Option<ReloadPolicy> {
+        None
+    }
+
+    // (path, content) pairs written to runtime/config/{id} when the runtime
+    // layout is created. Existing files are kept; *.tmpl files are rendered
+    // at every start.
+    fn config_templates(&self) -> Vec<(&'static str, &'static str)> {
+        Vec::new()
+    }
+
+    // Runs before every start; creates the data directory on the first one.
+    fn init_data(&self, _ctx: &DriverContext) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn dump(&self, _ctx: &DriverContext, _database: &str, _output: &Path) -> Result<(), String> {
+        Err(format!("{} does not support dumps", self.id()))
+    }
+
+    fn restore(&self, _ctx: &DriverContext, _database: &str, _input: &Path) -> Result<(), String> {
+        Err(format!("{} does not support restores", self.id()))
+    }
+
+    // Upstream download used when the runtime bundle lacks the binary.
+    fn official_source(&self, _version: &str, _os: &str, _arch: &str) -> Option<OfficialSource> {
+        None
+    }
+}
+
+// A rendered definition plus the resolved main binary, for drivers that run
+// the service's own tools (initdb, pg_dump, mysqldump, ...).
+#[derive(Debug, Clone)]
+pub struct DriverContext {
+    pub def: ServiceDefinition,
+    pub binary: PathBuf,
+    pub data_dir: PathBuf,
+    // PATH the service itself runs with, see RuntimeManager::scoped_path.
+    pub path: Option<String>,
+}
+
+impl DriverContext {
+    pub fn new(def: ServiceDefinition, binary: PathBuf, data_dir: PathBuf) -> Self {
+        Self {
+            def,
+            binary,
+            data_dir,
+            path: None,
+        }
+    }
+
+    pub fn with_path(mut self, path: String) -> Self {
+        self.path = Some(path);
+        self
+    }
+
+    // A tool shipped next to the main binary.
+    pub fn tool(&self, name: &str) -> PathBuf {
+        let file = exe_name(name, std::env::consts::OS);
+        match self.binary.parent() {
+            Some(parent) => parent.join(file),
+            None => PathBuf::from(file),
+        }
+    }
+
+    pub fn port(&self, name: &str) -> Result<u16, String> {
+        self.def
+            .ports
+            .iter()
+            .find(|port| port.name == name)
+            .map(|port| port.port)
+            .ok_or_else(|| format!("{} has no {name} port", self.def.id))
+    }
+
+    // Runs a tool to completion in the service's cwd, env and PATH, like the
+    // service's own commands; its stderr becomes the error.
+    pub fn run_tool(&self, name: &str, args: &[String]) -> Result<(), String> {
+        let tool = self.tool(name);
+        if !tool.exists() {
+            return Err(format!("{name} not found for {}", self.def.id));
+        }
+        let mut cmd = Command::new(&tool);
+        cmd.args(args).current_dir(&self.def.cwd).envs(&self.def.env);
+        if let Some(path) = &self.path {
+            cmd.env("PATH", path);
+        }
+        let output = cmd
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("failed to run {name}: {e}"))?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        Err(if stderr.is_empty() {
+            format!("{name} exited with {}", output.status)
+        } else {
+            stderr
+        })
+    }
+}
+
+pub fn all() -> Vec<Box<dyn ServiceDriver>> {
+    vec![
+        Box::new(php::PhpDriver),
+        Box::new(node::NodeDriver),
+        Box::new(postgres::PostgresDriver),
+        Box::new(mariadb::MariadbDriver),
+        Box::new(mailpit::MailpitDriver),
+    ]
+}
+
+pub fn find(id: &str) -> Option<Box<dyn ServiceDriver>> {
+    all().into_iter().find(|driver| driver.id() == id)
+}
+
+fn default_ports(driver: &dyn ServiceDriver) -> Vec<PortDef> {
+    vec![PortDef {
+        name: "main".to_string(),
+        port: driver.default_port(),
+        protocol: "tcp".to_string(),
+    }]
+}
+
+// The built-in ServiceDefinition for a driver.
+pub fn definition(driver: &dyn ServiceDriver) -> ServiceDefinition {
+    let id = driver.id();
+    let version = driver.default_version();
+    ServiceDefinition {
+        id: id.to_string(),
+        name: driver.name().to_string(),
+        binary: runtime::bin_path_for(id, version),
+        args: driver.args(),
+        env: driver.env(),
+        cwd: "${runtime}".to_string(),
+        ports: default_ports(driver),
+        depends_on: driver.depends_on(),
+        dependency_timeout_ms: 60_000,
+        health_check: driver.health_check(),
+        restart_policy: RestartPolicy::default(),
+        stop_policy: driver.stop_policy(),
+        reload_policy: driver.reload_policy(),
+        hooks: LifecycleHooks::default(),
+        log_format: driver.log_format().map(str::to_string),
+        version: Some(version.to_string()),
+    }
+}
+
+// The runtime manifest entry for a driver's default version.
+pub fn service_binary(driver: &dyn ServiceDriver, os: &str, arch: &str) -> ServiceBinary {
+    let id = driver.id();
+    let version = driver.default_version();
+    ServiceBinary {
+        name: id.to_string(),
+        version: version.to_string(),
+        os: os.to_string(),
+        arch: arch.to_string(),
+        checksum: String::new(),
+        size: 0,
+        bin_path: runtime::bin_path_for(id, version),
+        default_ports: default_ports(driver),
+        env: driver.env(),
+        args: driver.args(),
+    }
+}
+
+fn exe_name(name: &str, os: &str) -> String {
+    if os == "windows" {
+        format!("{name}.exe")
+    } else {
+        name.to_string()
+    }
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
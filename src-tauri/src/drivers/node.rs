@@ -0,0 +1,63 @@
+use super::{exe_name, strings, ServiceDriver};
+use crate::runtime::{archive_extension, ArchiveKind, OfficialSource};
+
+pub struct NodeDriver;
+
+impl ServiceDriver for NodeDriver {
+    fn id(&self) -> &'static str {
+        "node"
+    }
+
+    fn name(&self) -> &'static str {
+        "Node.js"
+    }
+
+    fn default_version(&self) -> &'static str {
+        "20.11.1"
+    }
+
+    fn default_port(&self) -> u16 {
+        3000
+    }
+
+    fn depends_on(&self) -> Vec<String> {
+        strings(&["postgres", "mariadb"])
+    }
+
+    fn log_format(&self) -> Option<&'static str> {
+        Some("json")
+    }
+
+    fn config_templates(&self) -> Vec<(&'static str, &'static str)> {
+        vec![("default.conf", "# node default config\n")]
+    }
+
+    fn official_source(&self, version: &str, os: &str, arch: &str) -> Option<OfficialSource> {
+        let platform = match os {
+            "windows" => "win",
+            "macos" => "darwin",
+            "linux" => "linux",
+            _ => return None,
+        };
+        let arch = match arch {
+            "x64" => "x64",
+            "arm64" => "arm64",
+            _ => return None,
+        };
+        let archive = if os == "windows" {
+            ArchiveKind::Zip
+        } else {
+            ArchiveKind::TarXz
+        };
+        let ext = archive_extension(archive);
+        let target_binary = exe_name("node", os);
+        Some(OfficialSource {
+            url: format!(
+                "https://nodejs.org/dist/v{version}/node-v{version}-{platform}-{arch}.{ext}"
+            ),
+            archive,
+            binary_names: vec![target_binary.clone()],
+            target_binary,
+        })
+    }
+}
@@ -0,0 +1,76 @@
+use super::{exe_name, strings, ServiceDriver};
+use crate::runtime::{ArchiveKind, OfficialSource};
+use std::collections::HashMap;
+
+pub struct PhpDriver;
+
+impl ServiceDriver for PhpDriver {
+    fn id(&self) -> &'static str {
+        "php"
+    }
+
+    fn name(&self) -> &'static str {
+        "PHP"
+    }
+
+    fn default_version(&self) -> &'static str {
+        "8.3.2"
+    }
+
+    fn default_port(&self) -> u16 {
+        9000
+    }
+
+    // Use built-in server for immediate "It works" experience without Nginx.
+    // It reads php.ini only at startup, so there is no reload policy;
+    // a php-fpm definition would reload with {"signal": "usr2"}.
+    fn args(&self) -> Vec<String> {
+        strings(&["-S", "0.0.0.0:${port.main}", "-t", "${runtime}/www"])
+    }
+
+    fn env(&self) -> HashMap<String, String> {
+        HashMap::from([(
+            "PHP_INI_SCAN_DIR".to_string(),
+            "${runtime}/config/php".to_string(),
+        )])
+    }
+
+    fn depends_on(&self) -> Vec<String> {
+        strings(&["postgres", "mariadb"])
+    }
+
+    fn log_format(&self) -> Option<&'static str> {
+        Some("php-server")
+    }
+
+    fn config_templates(&self) -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("default.conf", "# php default config\n"),
+            ("php.ini", "display_errors=On\nerror_reporting=E_ALL\n"),
+            (
+                "php-fpm.conf.tmpl",
+                "[global]\nerror_log=${log_dir}/php-fpm.log\ninclude=${runtime}/config/php/pool.d/*.conf\n",
+            ),
+            (
+                "pool.d/www.conf.tmpl",
+                "[www]\nlisten=127.0.0.1:${port.main}\npm=dynamic\npm.max_children=5\npm.start_servers=2\npm.min_spare_servers=1\npm.max_spare_servers=3\n",
+            ),
+        ]
+    }
+
+    // windows.php.net is the only upstream that ships prebuilt binaries.
+    fn official_source(&self, version: &str, os: &str, arch: &str) -> Option<OfficialSource> {
+        if os != "windows" || arch != "x64" {
+            return None;
+        }
+        let target_binary = exe_name("php", os);
+        Some(OfficialSource {
+            url: format!(
+                "https://windows.php.net/downloads/releases/php-{version}-Win32-vs16-x64.zip"
+            ),
+            archive: ArchiveKind::Zip,
+            binary_names: vec![target_binary.clone()],
+            target_binary,
+        })
+    }
+}
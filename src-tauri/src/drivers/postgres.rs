@@ -0,0 +1,176 @@
+use super::{exe_name, strings, DriverContext, ServiceDriver};
+use crate::models::{HealthCheck, ReloadPolicy, StopPolicy};
+use crate::runtime::{archive_extension, ArchiveKind, OfficialSource};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub struct PostgresDriver;
+
+impl PostgresDriver {
+    fn data_dir(ctx: &DriverContext) -> PathBuf {
+        ctx.def
+            .env
+            .get("PGDATA")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| ctx.data_dir.clone())
+    }
+
+    fn connection_args(ctx: &DriverContext) -> Result<Vec<String>, String> {
+        let port = ctx.port("main")?;
+        Ok(vec![
+            "-h".to_string(),
+            "127.0.0.1".to_string(),
+            "-p".to_string(),
+            port.to_string(),
+            "-U".to_string(),
+            "postgres".to_string(),
+        ])
+    }
+}
+
+impl ServiceDriver for PostgresDriver {
+    fn id(&self) -> &'static str {
+        "postgres"
+    }
+
+    fn name(&self) -> &'static str {
+        "Postgres"
+    }
+
+    fn default_version(&self) -> &'static str {
+        "16.2"
+    }
+
+    fn default_port(&self) -> u16 {
+        5432
+    }
+
+    // postgresql.conf is rendered from its template in runtime/config/postgres
+    // and points postgres at the pg_hba.conf next to it.
+    fn args(&self) -> Vec<String> {
+        strings(&[
+            "-D",
+            "${data_dir}",
+            "-p",
+            "${port.main}",
+            "-c",
+            "config_file=${runtime}/config/postgres/postgresql.conf",
+        ])
+    }
+
+    fn env(&self) -> HashMap<String, String> {
+        HashMap::from([("PGDATA".to_string(), "${data_dir}".to_string())])
+    }
+
+    fn log_format(&self) -> Option<&'static str> {
+        Some("postgres")
+    }
+
+    // An open port does not mean postgres accepts connections yet.
+    fn health_check(&self) -> HealthCheck {
+        HealthCheck {
+            kind: "exec".to_string(),
+            target: String::new(),
+            command: strings(&["pg_isready", "-h", "127.0.0.1", "-p", "${port.main}"]),
+            ..HealthCheck::default()
+        }
+    }
+
+    // SIGINT is postgres' "fast" shutdown; pg_ctl waits for the checkpoint to finish.
+    fn stop_policy(&self) -> StopPolicy {
+        StopPolicy {
+            signal: "int".to_string(),
+            command: strings(&["pg_ctl", "stop", "-D", "${data_dir}", "-m", "fast"]),
+            timeout_ms: 30_000,
+        }
+    }
+
+    // pg_ctl reload waits for the postmaster; SIGHUP covers a missing pg_ctl.
+    fn reload_policy(&self) -> Option<ReloadPolicy> {
+        Some(ReloadPolicy {
+            signal: Some("hup".to_string()),
+            command: strings(&["pg_ctl", "reload", "-D", "${data_dir}"]),
+            ..ReloadPolicy::default()
+        })
+    }
+
+    // Logging stays on stderr, which ends up in the service log.
+    fn config_templates(&self) -> Vec<(&'static str, &'static str)> {
+        vec![
+            (
+                "postgresql.conf.tmpl",
+                "port=${port.main}\nmax_connections=50\nhba_file='${runtime}/config/postgres/pg_hba.conf'\n",
+            ),
+            (
+                "pg_hba.conf",
+                "local all all trust\nhost all all 127.0.0.1/32 trust\nhost all all ::1/128 trust\n",
+            ),
+        ]
+    }
+
+    fn init_data(&self, ctx: &DriverContext) -> Result<(), String> {
+        let data_dir = Self::data_dir(ctx);
+        fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+        if data_dir.join("PG_VERSION").exists() {
+            return Ok(());
+        }
+        // The superuser has to match the "-U postgres" the tools connect with,
+        // whoever runs the app.
+        ctx.run_tool(
+            "initdb",
+            &[
+                "-D".to_string(),
+                data_dir.to_string_lossy().to_string(),
+                "-U".to_string(),
+                "postgres".to_string(),
+                "--auth=trust".to_string(),
+            ],
+        )
+    }
+
+    fn dump(&self, ctx: &DriverContext, database: &str, output: &Path) -> Result<(), String> {
+        let mut args = Self::connection_args(ctx)?;
+        args.extend([
+            "-f".to_string(),
+            output.to_string_lossy().to_string(),
+            database.to_string(),
+        ]);
+        ctx.run_tool("pg_dump", &args)
+    }
+
+    fn restore(&self, ctx: &DriverContext, database: &str, input: &Path) -> Result<(), String> {
+        let mut args = Self::connection_args(ctx)?;
+        args.extend([
+            "-v".to_string(),
+            "ON_ERROR_STOP=1".to_string(),
+            "-d".to_string(),
+            database.to_string(),
+            "-f".to_string(),
+            input.to_string_lossy().to_string(),
+        ]);
+        ctx.run_tool("psql", &args)
+    }
+
+    fn official_source(&self, version: &str, os: &str, arch: &str) -> Option<OfficialSource> {
+        let (platform, archive) = match os {
+            "windows" => ("windows-x64", ArchiveKind::Zip),
+            "linux" => ("linux-x64", ArchiveKind::TarGz),
+            "macos" => ("osx", ArchiveKind::Zip),
+            _ => return None,
+        };
+        if arch != "x64" {
+            return None;
+        }
+        let ext = archive_extension(archive);
+        let target_binary = exe_name("postgres", os);
+        Some(OfficialSource {
+            url: format!(
+                "https://get.enterprisedb.com/postgresql/postgresql-{version}-{platform}-binaries.{ext}"
+            ),
+            archive,
+            binary_names: vec![target_binary.clone()],
+            target_binary,
+        })
+    }
+}
@@ -0,0 +1,72 @@
+use crate::models::{LogEntry, ServiceState};
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum ServiceEvent {
+    #[serde(rename = "stateChanged")]
+    StateChanged {
+        previous: Option<String>,
+        state: ServiceState,
+    },
+    #[serde(rename = "crashed")]
+    Crashed {
+        service: String,
+        #[serde(rename = "exitCode")]
+        exit_code: Option<i32>,
+    },
+    #[serde(rename = "restarted")]
+    Restarted { service: String, attempt: u32 },
+    #[serde(rename = "health")]
+    Health {
+        service: String,
+        healthy: bool,
+        error: Option<String>,
+    },
+    #[serde(rename = "logLine")]
+    LogLine { entry: LogEntry },
+}
+
+impl ServiceEvent {
+    // Tauri event name the UI listens on.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ServiceEvent::StateChanged { .. } => "service:stateChanged",
+            ServiceEvent::Crashed { .. } => "service:crashed",
+            ServiceEvent::Restarted { .. } => "service:restarted",
+            ServiceEvent::Health { .. } => "service:health",
+            ServiceEvent::LogLine { .. } => "service:logLine",
+        }
+    }
+
+    pub fn service(&self) -> &str {
+        match self {
+            ServiceEvent::StateChanged { state, .. } => &state.id,
+            ServiceEvent::Crashed { service, .. }
+            | ServiceEvent::Restarted { service, .. }
+            | ServiceEvent::Health { service, .. } => service,
+            ServiceEvent::LogLine { entry } => &entry.service,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EventBus {
+    subscribers: Arc<Mutex<Vec<Sender<ServiceEvent>>>>,
+}
+
+impl EventBus {
+    pub fn subscribe(&self) -> Receiver<ServiceEvent> {
+        let (tx, rx) = channel();
+        self.subscribers.lock().expect("subscribers lock").push(tx);
+        rx
+    }
+
+    pub fn emit(&self, event: ServiceEvent) {
+        let mut subscribers = self.subscribers.lock().expect("subscribers lock");
+        // Dropped receivers are pruned on the next emit.
+        subscribers.retain(|tx| tx.send(event.clone()).is_ok());
+    }
+}
@@ -0,0 +1,140 @@
+use crate::models::HttpProbe;
+use crate::process;
+use regex::Regex;
+use reqwest::blocking::Client;
+use std::error::Error;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+pub fn probe_http(target: &str, timeout_ms: u64, probe: &HttpProbe) -> Result<(), String> {
+    let url = if target.contains("://") {
+        target.to_string()
+    } else {
+        format!("http://{target}")
+    };
+    let path = url_path(&url);
+    let timeout = Duration::from_millis(timeout_ms);
+    let client = Client::builder()
+        .timeout(timeout)
+        .connect_timeout(timeout)
+        .danger_accept_invalid_certs(!probe.tls_verify)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut request = client.get(&url);
+    for (key, value) in &probe.headers {
+        request = request.header(key.as_str(), value.as_str());
+    }
+    let response = request.send().map_err(|e| describe_error(&url, &e))?;
+
+    let status = response.status().as_u16();
+    let range = &probe.expected_status;
+    if status < range.from || status > range.to {
+        return Err(format!("{status} from {path}"));
+    }
+
+    if probe.body_contains.is_none() && probe.body_regex.is_none() {
+        return Ok(());
+    }
+    let body = response
+        .text()
+        .map_err(|e| format!("failed to read body from {path}: {e}"))?;
+    if let Some(needle) = &probe.body_contains {
+        if !body.contains(needle.as_str()) {
+            return Err(format!("{status} from {path}, body missing \"{needle}\""));
+        }
+    }
+    if let Some(pattern) = &probe.body_regex {
+        let regex = Regex::new(pattern).map_err(|e| format!("invalid body regex: {e}"))?;
+        if !regex.is_match(&body) {
+            return Err(format!("{status} from {path}, body does not match /{pattern}/"));
+        }
+    }
+    Ok(())
+}
+
+// Runs a client binary such as `pg_isready` and treats exit code 0 as healthy.
+// On failure the last line of output (stderr first) becomes the error.
+pub fn probe_exec(mut cmd: Command, timeout_ms: u64) -> Result<(), String> {
+    let program = cmd.get_program().to_string_lossy().to_string();
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    process::isolate(&mut cmd);
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("failed to run {program}: {e}"))?;
+    // Read both pipes while the check runs, so a chatty check cannot fill a
+    // pipe and block until the timeout.
+    let stdout = child.stdout.take().map(last_line);
+    let stderr = child.stderr.take().map(last_line);
+    let joined = |reader: Option<JoinHandle<Option<String>>>| {
+        reader.and_then(|reader| reader.join().ok().flatten())
+    };
+    if !process::wait_for_exit(&mut child, Duration::from_millis(timeout_ms)) {
+        let _ = process::kill_tree(child.id());
+        let _ = child.kill();
+        let _ = child.wait();
+        joined(stdout);
+        joined(stderr);
+        return Err(format!("{program} timed out after {timeout_ms}ms"));
+    }
+    let status = child.wait().map_err(|e| e.to_string())?;
+    let (stdout, stderr) = (joined(stdout), joined(stderr));
+    if status.success() {
+        return Ok(());
+    }
+    let status = match status.code() {
+        Some(code) => format!("{program} exited with code {code}"),
+        None => format!("{program} was terminated"),
+    };
+    match stderr.or(stdout) {
+        Some(line) => Err(format!("{status}: {line}")),
+        None => Err(status),
+    }
+}
+
+// Drains a pipe on its own thread and keeps its last non-empty line.
+fn last_line(stream: impl Read + Send + 'static) -> JoinHandle<Option<String>> {
+    thread::spawn(move || {
+        let mut last = None;
+        for line in BufReader::new(stream).split(b'\n').map_while(Result::ok) {
+            let line = String::from_utf8_lossy(&line).trim().to_string();
+            if !line.is_empty() {
+                last = Some(line);
+            }
+        }
+        last
+    })
+}
+
+fn url_path(url: &str) -> String {
+    let rest = url.split("://").nth(1).unwrap_or(url);
+    match rest.find('/') {
+        Some(index) => rest[index..].to_string(),
+        None => "/".to_string(),
+    }
+}
+
+fn describe_error(url: &str, err: &reqwest::Error) -> String {
+    let kind = if err.is_timeout() {
+        "timed out"
+    } else if err.is_connect() {
+        "connection failed"
+    } else {
+        "request failed"
+    };
+    // reqwest wraps the interesting part (refused, TLS, DNS) in the source chain.
+    let mut detail = None;
+    let mut source = err.source();
+    while let Some(inner) = source {
+        detail = Some(inner.to_string());
+        source = inner.source();
+    }
+    match detail {
+        Some(detail) => format!("{kind} for {url}: {detail}"),
+        None => format!("{kind} for {url}"),
+    }
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+// A day of probes at the 5s tick; older probes are dropped.
+const MAX_PROBES: usize = 17_280;
+
+// 5 minutes, 1 hour and 24 hours.
+pub const DEFAULT_WINDOWS_SECS: [u64; 3] = [300, 3_600, 86_400];
+
+// Upper bound for the raw probes a report carries; the stats always cover
+// every probe in their window.
+pub const MAX_PROBE_LIMIT: usize = 1_000;
+
+// One health check made while the service was meant to be up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthProbe {
+    // Seconds since the epoch.
+    pub ts: u64,
+    #[serde(rename = "latencyMs")]
+    pub latency_ms: u64,
+    pub healthy: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthStats {
+    #[serde(rename = "windowSecs")]
+    pub window_secs: u64,
+    pub probes: usize,
+    // Share of healthy probes; None without probes in the window.
+    #[serde(rename = "uptimePercent")]
+    pub uptime_percent: Option<f64>,
+    #[serde(rename = "meanLatencyMs")]
+    pub mean_latency_ms: Option<f64>,
+    // Changes between healthy and unhealthy inside the window.
+    pub flaps: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub service: String,
+    // The newest probes of the largest window, oldest first. Empty unless
+    // the caller asks for them.
+    pub probes: Vec<HealthProbe>,
+    pub windows: Vec<HealthStats>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HealthHistory {
+    probes: Arc<Mutex<HashMap<String, VecDeque<HealthProbe>>>>,
+}
+
+impl HealthHistory {
+    pub fn record(&self, id: &str, probe: HealthProbe) {
+        let mut probes = self.probes.lock().expect("health history lock");
+        let series = probes.entry(id.to_string()).or_default();
+        series.push_back(probe);
+        if series.len() > MAX_PROBES {
+            series.pop_front();
+        }
+    }
+
+    pub fn report(
+        &self,
+        id: &str,
+        now: u64,
+        windows_secs: &[u64],
+        probe_limit: usize,
+    ) -> HealthReport {
+        let probes: Vec<HealthProbe> = self
+            .probes
+            .lock()
+            .expect("health history lock")
+            .get(id)
+            .map(|series| series.iter().cloned().collect())
+            .unwrap_or_default();
+        report(id, &probes, now, windows_secs, probe_limit)
+    }
+}
+
+// `probe_limit` is capped at MAX_PROBE_LIMIT; 0 returns the stats only.
+pub fn report(
+    id: &str,
+    probes: &[HealthProbe],
+    now: u64,
+    windows_secs: &[u64],
+    probe_limit: usize,
+) -> HealthReport {
+    let longest = windows_secs.iter().copied().max().unwrap_or(0);
+    let recent = within(probes, now, longest);
+    let limit = probe_limit.min(MAX_PROBE_LIMIT);
+    HealthReport {
+        service: id.to_string(),
+        probes: recent[recent.len().saturating_sub(limit)..].to_vec(),
+        windows: windows_secs
+            .iter()
+            .map(|window| stats(probes, now, *window))
+            .collect(),
+    }
+}
+
+pub fn stats(probes: &[HealthProbe], now: u64, window_secs: u64) -> HealthStats {
+    let probes = within(probes, now, window_secs);
+    let count = probes.len();
+    let healthy = probes.iter().filter(|probe| probe.healthy).count();
+    let latency: u64 = probes.iter().map(|probe| probe.latency_ms).sum();
+    let flaps = probes
+        .windows(2)
+        .filter(|pair| pair[0].healthy != pair[1].healthy)
+        .count();
+    HealthStats {
+        window_secs,
+        probes: count,
+        uptime_percent: (count > 0).then(|| healthy as f64 * 100.0 / count as f64),
+        mean_latency_ms: (count > 0).then(|| latency as f64 / count as f64),
+        flaps,
+    }
+}
+
+// Probes are recorded in time order, so the window is a suffix.
+fn within(probes: &[HealthProbe], now: u64, window_secs: u64) -> &[HealthProbe] {
+    let since = now.saturating_sub(window_secs);
+    let start = probes.partition_point(|probe| probe.ts < since);
+    &probes[start..]
+}
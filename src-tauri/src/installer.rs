@@ -3,6 +3,7 @@ use std::sync::{Arc, Mutex};
 use std::fs;
 use std::path::PathBuf;
 
+use crate::drivers;
 use crate::runtime;
 use reqwest::blocking::Client;
 use reqwest::header::RANGE;
@@ -71,7 +72,8 @@ fn init_runtime_layout(root: &str) -> Result<(), String> {
     fs::create_dir_all(runtime.join("temp")).map_err(|e| e.to_string())?;
     fs::create_dir_all(runtime.join("cache")).map_err(|e| e.to_string())?;
 
-    for service in ["php", "node", "postgres", "mariadb", "mailpit"] {
+    for driver in drivers::all() {
+        let service = driver.id();
         fs::create_dir_all(runtime.join("data").join(service)).map_err(|e| e.to_string())?;
         fs::create_dir_all(runtime.join("logs").join(service)).map_err(|e| e.to_string())?;
         fs::create_dir_all(runtime.join("config").join(service)).map_err(|e| e.to_string())?;
@@ -79,11 +81,11 @@ fn init_runtime_layout(root: &str) -> Result<(), String> {
 
     let versions = runtime::default_versions();
     let (os, arch) = runtime::os_arch_tag();
-    for service in ["php", "node", "postgres", "mariadb", "mailpit"] {
-        if let Some(version) = versions.get(service) {
+    for driver in drivers::all() {
+        if let Some(version) = versions.get(driver.id()) {
             let bin_dir = runtime
                 .join("bin")
-                .join(service)
+                .join(driver.id())
                 .join(version)
                 .join(format!("{os}-{arch}"));
             fs::create_dir_all(bin_dir).map_err(|e| e.to_string())?;
@@ -101,46 +103,17 @@ fn init_runtime_layout(root: &str) -> Result<(), String> {
 }
 
 fn write_templates(runtime: &PathBuf) -> Result<(), String> {
-    let templates = [
-        ("php", "default.conf", "# php default config\n"),
-        ("php", "php.ini", "display_errors=On\nerror_reporting=E_ALL\n"),
-        (
-            "php",
-            "php-fpm.conf",
-            "[global]\nerror_log=runtime/logs/php/php-fpm.log\ninclude=runtime/config/php/pool.d/*.conf\n",
-        ),
-        (
-            "php",
-            "pool.d/www.conf",
-            "[www]\nlisten=127.0.0.1:9000\npm=dynamic\npm.max_children=5\npm.start_servers=2\npm.min_spare_servers=1\npm.max_spare_servers=3\n",
-        ),
-        ("node", "default.conf", "# node default config\n"),
-        (
-            "postgres",
-            "postgresql.conf",
-            "port=5432\nmax_connections=50\nlogging_collector=on\nlog_directory='runtime/logs/postgres'\n",
-        ),
-        (
-            "postgres",
-            "pg_hba.conf",
-            "local all all trust\nhost all all 127.0.0.1/32 trust\nhost all all ::1/128 trust\n",
-        ),
-        (
-            "mariadb",
-            "my.cnf",
-            "[mysqld]\nport=3306\ndatadir=runtime/data/mariadb\nlog_error=runtime/logs/mariadb/mariadb.log\n",
-        ),
-        ("mailpit", "default.conf", "# mailpit default config\n"),
-    ];
-    for (service, filename, content) in templates {
-        let path = runtime.join("config").join(service).join(filename);
-        if path.exists() {
-            continue;
-        }
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    for driver in drivers::all() {
+        for (filename, content) in driver.config_templates() {
+            let path = runtime.join("config").join(driver.id()).join(filename);
+            if path.exists() {
+                continue;
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            fs::write(path, content).map_err(|e| e.to_string())?;
         }
-        fs::write(path, content).map_err(|e| e.to_string())?;
     }
     Ok(())
 }
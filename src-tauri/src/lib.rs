@@ -1,13 +1,25 @@
 pub mod config;
-mod config_store;
+pub mod config_store;
 mod diagnostics;
+pub mod drivers;
+pub mod events;
+mod health;
+pub mod health_history;
+mod log_capture;
+pub mod log_files;
+pub mod log_parser;
+pub mod log_query;
 pub mod installer;
 pub mod models;
 pub mod runtime;
-mod metrics;
+pub mod metrics;
+mod pid_store;
+mod process;
+mod run_history;
 mod schema;
-mod secrets;
+pub mod secrets;
 pub mod service_manager;
+pub mod template;
 mod updater;
 mod projects;
 pub mod tooling;
@@ -21,14 +33,17 @@ use std::sync::{Arc, Mutex};
 use tauri::{
     menu::{Menu, MenuItem},
     tray::{MouseButton, TrayIconBuilder, TrayIconEvent},
-    Manager, State,
+    Emitter, Manager, State,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::process::Command;
+use std::sync::mpsc::Receiver;
 
 #[derive(Debug)]
 struct AppState {
     services: Arc<Mutex<ServiceManager>>,
+    // Service id -> labels of the windows streaming its log lines.
+    log_subscriptions: Arc<Mutex<HashMap<String, HashSet<String>>>>,
     config: Arc<Mutex<config_store::ConfigStore>>,
     secrets: Arc<Mutex<secrets::SecretsStore>>,
     installer: Arc<installer::Installer>,
@@ -38,6 +53,8 @@ struct AppState {
     php_config: Arc<Mutex<config::php::PhpConfigManager>>,
     db_manager: Arc<Mutex<database::DatabaseManager>>,
     task_manager: Arc<Mutex<task_manager::TaskManager>>,
+    // Problems found in app/config/services.d at startup.
+    definition_errors: Vec<String>,
 }
 
 #[tauri::command]
@@ -66,8 +83,16 @@ fn runtime_list_versions(service: String) -> Vec<String> {
 
 #[tauri::command]
 fn db_dump(state: State<'_, AppState>, service: String, db_name: String, path: String) -> Result<String, String> {
+    let context = state.services.lock().expect("service manager lock").driver_context(&service)?;
     let manager = state.db_manager.lock().expect("db manager lock");
-    manager.dump(&service, &db_name, std::path::PathBuf::from(path))
+    manager.dump(&context, &db_name, std::path::PathBuf::from(path))
+}
+
+#[tauri::command]
+fn db_restore(state: State<'_, AppState>, service: String, db_name: String, path: String) -> Result<String, String> {
+    let context = state.services.lock().expect("service manager lock").driver_context(&service)?;
+    let manager = state.db_manager.lock().expect("db manager lock");
+    manager.restore(&context, &db_name, std::path::PathBuf::from(path))
 }
 
 #[tauri::command]
@@ -158,7 +183,7 @@ fn services_start(state: State<'_, AppState>, id: String) -> Result<models::Serv
     let result = services.start_with_config(&id, service_config);
     
     if let Ok(ref state) = result {
-        telemetry::track_service_start(&id, state.state == "running");
+        telemetry::track_service_start(&id, state.state != "error");
     } else if let Err(ref err) = result {
         telemetry::track_service_error(&id, err);
     }
@@ -167,9 +192,35 @@ fn services_start(state: State<'_, AppState>, id: String) -> Result<models::Serv
 }
 
 #[tauri::command]
-fn services_stop(state: State<'_, AppState>, id: String) -> Result<models::ServiceState, String> {
+fn services_stop(
+    state: State<'_, AppState>,
+    id: String,
+    with_dependents: Option<bool>,
+) -> Result<models::ServiceState, String> {
+    let mut services = state.services.lock().expect("service manager lock");
+    if with_dependents.unwrap_or(false) {
+        services.stop_with_dependents(&id)
+    } else {
+        services.stop(&id)
+    }
+}
+
+#[tauri::command]
+fn services_leaked(state: State<'_, AppState>) -> Vec<models::LeakedProcess> {
+    let services = state.services.lock().expect("service manager lock");
+    services.leaked_processes()
+}
+
+#[tauri::command]
+fn services_kill_leaked(state: State<'_, AppState>) -> Vec<models::LeakedProcess> {
     let mut services = state.services.lock().expect("service manager lock");
-    services.stop(&id)
+    services.kill_leaked()
+}
+
+#[tauri::command]
+fn services_dependents(state: State<'_, AppState>, id: String) -> Vec<String> {
+    let services = state.services.lock().expect("service manager lock");
+    services.dependents(&id)
 }
 
 #[tauri::command]
@@ -193,18 +244,20 @@ fn services_apply_config(
     let config = state.config.lock().expect("config store lock");
     let mut service_config = config.load_service_config(&id)?;
     resolve_service_ports(&config, &id, &mut service_config)?;
-    services.restart_with_config(&id, service_config)
+    services.restart_with_reason(&id, service_config, "config applied")
 }
 
 #[tauri::command]
 fn services_apply_config_no_restart(
     state: State<'_, AppState>,
     id: String,
-) -> Result<models::ServiceState, String> {
+    allow_restart: Option<bool>,
+) -> Result<models::ReloadOutcome, String> {
     let mut services = state.services.lock().expect("service manager lock");
     let config = state.config.lock().expect("config store lock");
-    let service_config = config.load_service_config(&id)?;
-    services.apply_config_no_restart(&id, service_config)
+    let mut service_config = config.load_service_config(&id)?;
+    resolve_service_ports(&config, &id, &mut service_config)?;
+    services.reload_with_config(&id, service_config, allow_restart.unwrap_or(false))
 }
 
 #[tauri::command]
@@ -217,6 +270,59 @@ fn services_logs(
     services.logs(&id, tail)
 }
 
+#[tauri::command]
+fn services_logs_subscribe(
+    window: tauri::Window,
+    state: State<'_, AppState>,
+    id: String,
+    tail: usize,
+) -> models::LogSnapshot {
+    // Subscribe before taking the snapshot: every line newer than the
+    // snapshot is then forwarded to this window. Lines can still be in both;
+    // the window drops events with seq <= lastSeq.
+    let services = state.services.lock().expect("service manager lock");
+    state
+        .log_subscriptions
+        .lock()
+        .expect("log subscriptions lock")
+        .entry(id.clone())
+        .or_default()
+        .insert(window.label().to_string());
+    services.log_snapshot(&id, tail)
+}
+
+#[tauri::command]
+fn services_logs_unsubscribe(window: tauri::Window, state: State<'_, AppState>, id: String) {
+    let mut subscriptions = state
+        .log_subscriptions
+        .lock()
+        .expect("log subscriptions lock");
+    if let Some(windows) = subscriptions.get_mut(&id) {
+        windows.remove(window.label());
+        if windows.is_empty() {
+            subscriptions.remove(&id);
+        }
+    }
+}
+
+// A closed window stops streaming every service it subscribed to.
+fn unsubscribe_window(subscriptions: &Mutex<HashMap<String, HashSet<String>>>, label: &str) {
+    let mut subscriptions = subscriptions.lock().expect("log subscriptions lock");
+    subscriptions.retain(|_, windows| {
+        windows.remove(label);
+        !windows.is_empty()
+    });
+}
+
+#[tauri::command]
+fn logs_query(
+    state: State<'_, AppState>,
+    query: log_query::LogQuery,
+) -> Result<log_query::LogPage, String> {
+    let services = state.services.lock().expect("service manager lock");
+    services.query_logs(&query)
+}
+
 #[tauri::command]
 fn services_log_path(state: State<'_, AppState>, id: String) -> String {
     let services = state.services.lock().expect("service manager lock");
@@ -278,20 +384,84 @@ fn system_open_file(path: String) -> Result<(), String> {
 
 #[tauri::command]
 fn services_health(state: State<'_, AppState>, id: String) -> Result<String, String> {
+    // Probe after releasing the manager lock; a probe can take its full timeout.
+    let task = {
+        let services = state.services.lock().expect("service manager lock");
+        services.health_task(&id)?
+    };
+    task.run()
+}
+
+#[tauri::command]
+fn services_launch_plan(state: State<'_, AppState>, id: String) -> Result<models::LaunchPlan, String> {
     let services = state.services.lock().expect("service manager lock");
-    services.health(&id)
+    let config = state.config.lock().expect("config store lock");
+    let service_config = planned_service_config(&config, &id)?;
+    services.launch_plan(&id, &service_config)
 }
 
 #[tauri::command]
-fn health_summary(state: State<'_, AppState>) -> HashMap<String, String> {
+fn services_runs(
+    state: State<'_, AppState>,
+    id: String,
+    limit: Option<usize>,
+) -> Vec<models::RunRecord> {
     let services = state.services.lock().expect("service manager lock");
+    services.runs(&id, limit.unwrap_or(20))
+}
+
+#[tauri::command]
+fn services_run(
+    state: State<'_, AppState>,
+    id: String,
+    run: u64,
+) -> Result<models::RunRecord, String> {
+    let services = state.services.lock().expect("service manager lock");
+    services.run(&id, run)
+}
+
+#[tauri::command]
+fn services_health_history(
+    state: State<'_, AppState>,
+    id: String,
+    windows_secs: Option<Vec<u64>>,
+    probe_limit: Option<usize>,
+) -> Result<health_history::HealthReport, String> {
+    let services = state.services.lock().expect("service manager lock");
+    let windows = windows_secs.unwrap_or_else(|| health_history::DEFAULT_WINDOWS_SECS.to_vec());
+    services.health_history(&id, &windows, probe_limit.unwrap_or(0))
+}
+
+#[tauri::command]
+fn health_summary(state: State<'_, AppState>) -> HashMap<String, String> {
+    let tasks: Vec<(String, Result<service_manager::HealthTask, String>)> = {
+        let services = state.services.lock().expect("service manager lock");
+        services
+            .definitions()
+            .iter()
+            .map(|def| (def.id.clone(), services.health_task(&def.id)))
+            .collect()
+    };
     let mut summary = HashMap::new();
-    for def in config::default_services() {
-        let status = services.health(&def.id).unwrap_or_else(|_| "error".to_string());
-        summary.insert(def.id, status);
+    for (id, task) in tasks {
+        let status = task
+            .and_then(|task| task.run())
+            .unwrap_or_else(|_| "error".to_string());
+        summary.insert(id, status);
     }
     summary
 }
+
+#[tauri::command]
+fn services_definitions(state: State<'_, AppState>) -> Vec<models::ServiceDefinition> {
+    let services = state.services.lock().expect("service manager lock");
+    services.definitions().to_vec()
+}
+
+#[tauri::command]
+fn services_definition_errors(state: State<'_, AppState>) -> Vec<String> {
+    state.definition_errors.clone()
+}
 #[tauri::command]
 fn config_get_app(state: State<'_, AppState>) -> Result<config_store::AppConfig, String> {
     let config = state.config.lock().expect("config store lock");
@@ -356,6 +526,85 @@ fn config_reset_service(
     config.reset_service_config(&id)
 }
 
+#[tauri::command]
+fn profiles_list(state: State<'_, AppState>) -> Result<Vec<config_store::Profile>, String> {
+    let config = state.config.lock().expect("config store lock");
+    config.list_profiles()
+}
+
+#[tauri::command]
+fn profiles_save(
+    state: State<'_, AppState>,
+    profile: config_store::Profile,
+) -> Result<(), String> {
+    let services = state.services.lock().expect("service manager lock");
+    for member in &profile.services {
+        if !services.definitions().iter().any(|def| def.id == member.id) {
+            return Err(format!("service not found: {}", member.id));
+        }
+    }
+    let config = state.config.lock().expect("config store lock");
+    config.save_profile(&profile)
+}
+
+#[tauri::command]
+fn profiles_delete(state: State<'_, AppState>, name: String) -> Result<(), String> {
+    let config = state.config.lock().expect("config store lock");
+    config.delete_profile(&name)
+}
+
+#[tauri::command]
+fn profiles_activate(
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<Vec<models::ServiceState>, String> {
+    let mut services = state.services.lock().expect("service manager lock");
+    let config = state.config.lock().expect("config store lock");
+    let profile = config.load_profile(&name)?;
+    let mut members = Vec::new();
+    for member in &profile.services {
+        let mut service_config = config.load_service_config(&member.id)?;
+        resolve_service_ports(&config, &member.id, &mut service_config)?;
+        // Profile overrides stay out of the saved service config.
+        member.apply(&mut service_config);
+        if let Some(port) = member.ports.get("main") {
+            let resolved = config.resolve_port(&member.id, *port)?;
+            service_config.ports.insert("main".to_string(), resolved);
+        }
+        members.push(service_config);
+    }
+    let states = services.activate_profile(members)?;
+    config.set_active_profile(Some(&name))?;
+    Ok(states)
+}
+
+#[tauri::command]
+fn profiles_active(state: State<'_, AppState>) -> Option<String> {
+    let config = state.config.lock().expect("config store lock");
+    config.load_app_config_or_default().active_profile
+}
+
+// The port a service was configured with, falling back to its definition.
+fn service_main_port(state: &State<'_, AppState>, id: &str) -> u16 {
+    let configured = state
+        .config
+        .lock()
+        .expect("config store lock")
+        .load_service_config(id)
+        .ok()
+        .and_then(|config| config.ports.get("main").copied());
+    configured.unwrap_or_else(|| {
+        let services = state.services.lock().expect("service manager lock");
+        services
+            .definitions()
+            .iter()
+            .find(|def| def.id == id)
+            .and_then(|def| def.ports.iter().find(|port| port.name == "main"))
+            .map(|port| port.port)
+            .unwrap_or(0)
+    })
+}
+
 fn resolve_service_ports(
     config: &config_store::ConfigStore,
     id: &str,
@@ -374,6 +623,20 @@ fn resolve_service_ports(
     Ok(())
 }
 
+// The ServiceConfig a start would use, without allocating or saving ports.
+fn planned_service_config(
+    config: &config_store::ConfigStore,
+    id: &str,
+) -> Result<config_store::ServiceConfig, String> {
+    let mut service_config = config.load_service_config(id)?;
+    if !service_config.ports.contains_key("main") {
+        if let Some(port) = config.load_port_registry()?.assigned.get(id) {
+            service_config.ports.insert("main".to_string(), *port);
+        }
+    }
+    Ok(service_config)
+}
+
 #[tauri::command]
 fn secrets_get(state: State<'_, AppState>, key: String) -> Option<String> {
     let secrets = state.secrets.lock().expect("secrets lock");
@@ -388,12 +651,16 @@ fn secrets_set(state: State<'_, AppState>, key: String, value: String) -> Result
 
 #[tauri::command]
 fn diagnostics_create(state: State<'_, AppState>) -> Result<String, String> {
+    let ids: Vec<String> = {
+        let services = state.services.lock().expect("service manager lock");
+        services.definitions().iter().map(|def| def.id.clone()).collect()
+    };
     let config = state.config.lock().expect("config store lock");
     let app_config = config.load_app_config_or_default();
     let service_configs = {
         let mut values = Vec::new();
-        for def in &config::default_services() {
-            if let Ok(cfg) = config.load_service_config(&def.id) {
+        for id in &ids {
+            if let Ok(cfg) = config.load_service_config(id) {
                 if let Ok(value) = serde_json::to_value(cfg) {
                     values.push(value);
                 }
@@ -402,9 +669,26 @@ fn diagnostics_create(state: State<'_, AppState>) -> Result<String, String> {
         values
     };
     let mut services = state.services.lock().expect("service manager lock");
+    let mut reports = diagnostics::ServiceReports::default();
+    for id in &ids {
+        match planned_service_config(&config, id).and_then(|cfg| services.launch_plan(id, &cfg)) {
+            Ok(plan) => reports.launch_plans.push(plan),
+            Err(err) => reports.launch_plan_errors.push(format!("{id}: {err}")),
+        }
+        if let Ok(report) = services.health_history(id, &health_history::DEFAULT_WINDOWS_SECS, 0) {
+            reports.health_history.push(report);
+        }
+    }
     let states = services.list().clone();
     let logs = services.snapshot_logs();
-    diagnostics::write_bundle(".", app_config, states, logs, service_configs)
+    diagnostics::write_bundle(
+        ".",
+        app_config,
+        states,
+        logs,
+        service_configs,
+        reports,
+    )
 }
 
 #[tauri::command]
@@ -413,6 +697,16 @@ fn metrics_snapshot(state: State<'_, AppState>) -> Result<metrics::MetricsSnapsh
     metrics::snapshot(&config)
 }
 
+#[tauri::command]
+fn metrics_services(state: State<'_, AppState>) -> Vec<metrics::ServiceMetrics> {
+    // Sampling walks the process table; don't hold the manager lock for it.
+    let roots = {
+        let services = state.services.lock().expect("service manager lock");
+        services.tracked_pids()
+    };
+    metrics::service_metrics(&roots)
+}
+
 #[tauri::command]
 fn runtime_get_manifest(_state: State<'_, AppState>) -> Result<runtime::RuntimeManifest, String> {
     let runtime = runtime::RuntimeManager::new(".");
@@ -562,10 +856,11 @@ fn projects_save(
     // Auto-sync domain
     if !project.domain.is_empty() {
         let root = std::path::PathBuf::from(".");
+        let service = if project.stack == "node" { "node" } else { "php" };
         let mapping = tooling::DomainMapping {
             domain: project.domain.clone(),
             project_id: project.id.clone(),
-            target_port: if project.stack == "node" { 3000 } else { 8000 }, // Default ports
+            target_port: service_main_port(&state, service),
         };
         tooling::domains_upsert(&root, mapping)?;
         
@@ -659,6 +954,29 @@ fn check_port_availability(port: u16) -> bool {
     std::net::TcpListener::bind(("127.0.0.1", port)).is_ok()
 }
 
+fn forward_service_events(
+    app: tauri::AppHandle,
+    events: Receiver<events::ServiceEvent>,
+    log_subscriptions: Arc<Mutex<HashMap<String, HashSet<String>>>>,
+) {
+    for event in events {
+        if let events::ServiceEvent::LogLine { entry } = &event {
+            // Log lines only go to the windows that subscribed to the service.
+            let windows = log_subscriptions
+                .lock()
+                .expect("log subscriptions lock")
+                .get(&entry.service)
+                .cloned()
+                .unwrap_or_default();
+            for label in windows {
+                let _ = app.emit_to(label.as_str(), event.name(), &event);
+            }
+            continue;
+        }
+        let _ = app.emit(event.name(), &event);
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // In dev (cargo run), CWD is src-tauri. We want data in project root "app" to avoid watch loops.
@@ -675,24 +993,34 @@ pub fn run() {
     } else {
         std::path::PathBuf::from(".")
     };
+    // Services get ${root} and friends as absolute paths, so a later change of
+    // working directory cannot move their data.
+    let root_path = std::path::absolute(&root_path).unwrap_or(root_path);
 
     let runtime = runtime::RuntimeManager::new(root_path.clone());
     let _ = runtime.ensure_manifest();
     metrics::init_start();
-    let definitions = config::default_services();
+    let (definitions, definition_errors) = config::load_services(&root_path);
+    for err in &definition_errors {
+        eprintln!("custom service skipped: {err}");
+    }
     let log_root = root_path.join("app/logs/services");
-    let service_manager = ServiceManager::new(runtime.clone(), definitions, log_root);
-    let config_store = config_store::ConfigStore::new(root_path.clone());
+    let mut service_manager = ServiceManager::new(runtime.clone(), definitions, log_root);
+    service_manager.set_state_dir(root_path.join("app/state"));
     let secrets_store = secrets::SecretsStore::new(root_path.clone()).unwrap_or_else(|err| {
         eprintln!("secrets init failed: {err}");
         secrets::SecretsStore::new_in_memory()
     });
+    let secrets_store = Arc::new(Mutex::new(secrets_store));
+    service_manager.set_secrets(Arc::clone(&secrets_store));
+    service_manager.reattach();
+    let config_store = config_store::ConfigStore::new(root_path.clone());
     let installer = Arc::new(installer::Installer::new());
     let updater = Arc::new(updater::Updater::new());
     let update_progress = Arc::new(Mutex::new(updater::default_progress()));
     let projects = Arc::new(Mutex::new(projects::ProjectStore::new(root_path.clone())));
     let php_config = Arc::new(Mutex::new(config::php::PhpConfigManager::new(root_path.clone())));
-    let db_manager = Arc::new(Mutex::new(database::DatabaseManager::new(runtime.clone())));
+    let db_manager = Arc::new(Mutex::new(database::DatabaseManager::new()));
     let task_manager = Arc::new(Mutex::new(task_manager::TaskManager::new(runtime)));
 
     if let Ok(app_config) = config_store.load_app_config() {
@@ -705,18 +1033,41 @@ pub fn run() {
     if let Err(err) = config_store.load_port_registry() {
         eprintln!("port registry invalid: {err}");
     }
-    for def in &config::default_services() {
+    for def in service_manager.definitions().to_vec() {
         if let Err(err) = config_store.ensure_service_config(&def.id) {
             eprintln!("service config init failed for {}: {}", def.id, err);
         }
+        if let Ok(service_config) = config_store.load_service_config(&def.id) {
+            service_manager.set_log_rotation(&def.id, service_config.log_rotation);
+        }
     }
+    let config = Arc::new(Mutex::new(config_store));
+    let config_bg = Arc::clone(&config);
+    let log_files = service_manager.log_files();
+    let logs_root = root_path.join("app/logs");
+    std::thread::spawn(move || loop {
+        // Read on every pass so a changed budget applies without a restart.
+        let log_budget_mb = config_bg
+            .lock()
+            .expect("config store lock")
+            .load_app_config_or_default()
+            .log_disk_budget_mb;
+        log_files.prune_expired();
+        log_files.enforce_disk_budget(&logs_root, log_budget_mb * 1024 * 1024);
+        std::thread::sleep(std::time::Duration::from_secs(600));
+    });
+    let service_events = service_manager.subscribe();
+    let log_subscriptions = Arc::new(Mutex::new(HashMap::new()));
+    let log_subscriptions_bg = Arc::clone(&log_subscriptions);
     let services = Arc::new(Mutex::new(service_manager));
     let services_bg = Arc::clone(&services);
     std::thread::spawn(move || loop {
-        {
+        // Probes run after the lock is released so commands are not held up.
+        let round = {
             let mut manager = services_bg.lock().expect("service manager lock");
-            manager.tick();
-        }
+            manager.begin_tick()
+        };
+        round.run();
         std::thread::sleep(std::time::Duration::from_secs(5));
     });
 
@@ -724,8 +1075,9 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .manage(AppState {
             services,
-            config: Arc::new(Mutex::new(config_store)),
-            secrets: Arc::new(Mutex::new(secrets_store)),
+            log_subscriptions,
+            config,
+            secrets: secrets_store,
             installer,
             updater,
             update_progress,
@@ -733,18 +1085,37 @@ pub fn run() {
             php_config,
             db_manager,
             task_manager,
+            definition_errors,
+        })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Destroyed = event {
+                let state = window.state::<AppState>();
+                unsubscribe_window(&state.log_subscriptions, window.label());
+            }
         })
         .invoke_handler(tauri::generate_handler![
             services_list,
+            services_definitions,
+            services_definition_errors,
             services_start,
             services_stop,
+            services_dependents,
+            services_leaked,
+            services_kill_leaked,
             services_restart,
             services_apply_config,
             services_apply_config_no_restart,
             services_logs,
+            services_logs_subscribe,
+            services_logs_unsubscribe,
             services_log_path,
             services_health,
+            services_launch_plan,
+            services_runs,
+            services_health_history,
+            services_run,
             health_summary,
+            logs_query,
             logs_export,
             logs_clear,
             system_open_file,
@@ -756,10 +1127,16 @@ pub fn run() {
             config_get_service,
             config_set_service,
             config_reset_service,
+            profiles_list,
+            profiles_save,
+            profiles_delete,
+            profiles_activate,
+            profiles_active,
             secrets_get,
             secrets_set,
             diagnostics_create,
             metrics_snapshot,
+            metrics_services,
             runtime_get_manifest,
             runtime_get_manifest_raw,
             runtime_save_manifest_raw,
@@ -770,6 +1147,7 @@ pub fn run() {
             runtime_download_status,
             runtime_list_versions,
             db_dump,
+            db_restore,
             task_list_scripts,
             task_run,
             task_stop,
@@ -800,6 +1178,11 @@ pub fn run() {
             open_terminal
         ])
         .setup(|app| {
+            let handle = app.handle().clone();
+            std::thread::spawn(move || {
+                forward_service_events(handle, service_events, log_subscriptions_bg)
+            });
+
             let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
             let show_i = MenuItem::with_id(app, "show", "Show Dashboard", true, None::<&str>)?;
             let menu = Menu::with_items(app, &[&show_i, &quit_i])?;
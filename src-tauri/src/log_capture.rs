@@ -0,0 +1,282 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Once this much of a capture file has been read, the read part is handed
+// back to the file system; its lines are already in the rotated service log.
+const MAX_CAPTURE_BYTES: u64 = 8 * 1024 * 1024;
+
+// Services write stdout and stderr to {root}/capture/{id}.stdout and
+// {id}.stderr instead of pipes, so they never write into a pipe that went
+// away with the app, and a later session can pick their output up again.
+pub fn stdout_path(root: &Path, id: &str) -> PathBuf {
+    root.join("capture").join(format!("{id}.stdout"))
+}
+
+pub fn stderr_path(root: &Path, id: &str) -> PathBuf {
+    root.join("capture").join(format!("{id}.stderr"))
+}
+
+// Where a tail saves how far it has read a capture file, so the next
+// session can continue from there: {id}.stdout.pos.
+fn position_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".pos");
+    PathBuf::from(name)
+}
+
+// Opens a capture file for a new run. Append mode keeps the service writing
+// at the end of the file.
+pub fn create(path: &Path) -> Result<File, String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let _ = fs::remove_file(position_path(path));
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+    file.set_len(0).map_err(|e| e.to_string())?;
+    Ok(file)
+}
+
+// Reads the lines appended to a capture file since the last read.
+pub struct Tail {
+    path: PathBuf,
+    offset: u64,
+    partial: Vec<u8>,
+    // Offset up to which the file was last reclaimed, or tried to be.
+    reclaimed: u64,
+    // Position last written to the position file.
+    saved: u64,
+}
+
+impl Tail {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            offset: 0,
+            partial: Vec::new(),
+            reclaimed: 0,
+            saved: 0,
+        }
+    }
+
+    // Continues where the previous session stopped reading, so output an
+    // adopted service wrote while no app was running is logged as well.
+    // Without a usable saved position it starts at the end of the file.
+    pub fn resume(path: PathBuf) -> Self {
+        let len = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let offset = fs::read_to_string(position_path(&path))
+            .ok()
+            .and_then(|raw| raw.trim().parse::<u64>().ok())
+            .filter(|offset| *offset <= len)
+            .unwrap_or(len);
+        Self {
+            offset,
+            reclaimed: offset,
+            saved: offset,
+            ..Self::new(path)
+        }
+    }
+
+    // Everything before the returned offset has been handed out as lines.
+    fn consumed(&self) -> u64 {
+        self.offset - self.partial.len() as u64
+    }
+
+    fn save_position(&mut self) {
+        let consumed = self.consumed();
+        if consumed == self.saved {
+            return;
+        }
+        if fs::write(position_path(&self.path), consumed.to_string()).is_ok() {
+            self.saved = consumed;
+        }
+    }
+
+    pub fn read_lines(&mut self) -> Vec<String> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(_) => return Vec::new(),
+        };
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        if len < self.offset {
+            // Truncated under us: start over.
+            self.offset = 0;
+            self.partial.clear();
+            self.reclaimed = 0;
+        }
+        let mut raw = Vec::new();
+        if file.seek(SeekFrom::Start(self.offset)).is_err() || file.read_to_end(&mut raw).is_err() {
+            return Vec::new();
+        }
+        self.offset += raw.len() as u64;
+        self.partial.extend_from_slice(&raw);
+        let mut lines = Vec::new();
+        while let Some(end) = self.partial.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.partial.drain(..=end).collect();
+            lines.push(decode(&line[..line.len() - 1]));
+        }
+        self.save_position();
+        if self.offset - self.reclaimed > MAX_CAPTURE_BYTES {
+            // The service keeps appending while this runs, so the file is
+            // never truncated or moved; only its read part is deallocated.
+            self.reclaimed = reclaim(&self.path, self.offset).unwrap_or(self.offset);
+        }
+        lines
+    }
+
+    // A last line without a newline, once nothing will be appended to it.
+    pub fn rest(&mut self) -> Option<String> {
+        if self.partial.is_empty() {
+            return None;
+        }
+        let line = decode(&self.partial);
+        self.partial.clear();
+        self.save_position();
+        Some(line)
+    }
+}
+
+// Deallocates the first `len` bytes of a file while keeping its size and
+// offsets, so reads and appends carry on unaffected. Returns how far the
+// file is reclaimed now; None where that is not supported.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+fn reclaim(path: &Path, len: u64) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    use std::os::unix::io::AsRawFd;
+
+    let file = OpenOptions::new().write(true).open(path).ok()?;
+    // macOS only punches whole blocks.
+    let block = file.metadata().ok()?.blksize().max(1);
+    let len = len - len % block;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    let punched = unsafe {
+        libc::fallocate(
+            file.as_raw_fd(),
+            libc::FALLOC_FL_PUNCH_HOLE | libc::FALLOC_FL_KEEP_SIZE,
+            0,
+            len as libc::off_t,
+        ) == 0
+    };
+    #[cfg(target_os = "macos")]
+    let punched = unsafe {
+        let hole = libc::fpunchhole_t {
+            fp_flags: 0,
+            reserved: 0,
+            fp_offset: 0,
+            fp_length: len as libc::off_t,
+        };
+        libc::fcntl(file.as_raw_fd(), libc::F_PUNCHHOLE, &hole as *const libc::fpunchhole_t) == 0
+    };
+    punched.then_some(len)
+}
+
+#[cfg(windows)]
+fn reclaim(path: &Path, len: u64) -> Option<u64> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::HANDLE;
+    use windows_sys::Win32::System::Ioctl::{
+        FILE_ZERO_DATA_INFORMATION, FSCTL_SET_SPARSE, FSCTL_SET_ZERO_DATA,
+    };
+    use windows_sys::Win32::System::IO::DeviceIoControl;
+
+    let file = OpenOptions::new().write(true).open(path).ok()?;
+    let handle = file.as_raw_handle() as HANDLE;
+    let zero = FILE_ZERO_DATA_INFORMATION {
+        FileOffset: 0,
+        BeyondFinalZero: len as i64,
+    };
+    let mut returned = 0u32;
+    // Zeroed ranges of a sparse file are deallocated.
+    let zeroed = unsafe {
+        DeviceIoControl(
+            handle,
+            FSCTL_SET_SPARSE,
+            std::ptr::null(),
+            0,
+            std::ptr::null_mut(),
+            0,
+            &mut returned,
+            std::ptr::null_mut(),
+        ) != 0
+            && DeviceIoControl(
+                handle,
+                FSCTL_SET_ZERO_DATA,
+                &zero as *const FILE_ZERO_DATA_INFORMATION as *const _,
+                std::mem::size_of::<FILE_ZERO_DATA_INFORMATION>() as u32,
+                std::ptr::null_mut(),
+                0,
+                &mut returned,
+                std::ptr::null_mut(),
+            ) != 0
+    };
+    zeroed.then_some(len)
+}
+
+// Elsewhere the file grows until the service's next start recreates it.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    windows
+)))]
+fn reclaim(_path: &Path, _len: u64) -> Option<u64> {
+    None
+}
+
+fn decode(raw: &[u8]) -> String {
+    let line = String::from_utf8_lossy(raw);
+    line.strip_suffix('\r').unwrap_or(&line).to_string()
+}
+
+// Polls a service's capture files on its own thread until the service is
+// gone or `finish` is called.
+#[derive(Debug)]
+pub struct Follower {
+    stop: Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+impl Follower {
+    pub fn spawn(
+        mut tails: Vec<(&'static str, Tail)>,
+        alive: impl Fn() -> bool + Send + 'static,
+        mut on_line: impl FnMut(&'static str, String) + Send + 'static,
+    ) -> Self {
+        let (stop, stopped) = mpsc::channel();
+        let handle = thread::spawn(move || loop {
+            let last = match stopped.recv_timeout(POLL_INTERVAL) {
+                Err(RecvTimeoutError::Timeout) => !alive(),
+                _ => true,
+            };
+            for (stream, tail) in tails.iter_mut() {
+                for line in tail.read_lines() {
+                    on_line(stream, line);
+                }
+            }
+            if last {
+                for (stream, tail) in tails.iter_mut() {
+                    if let Some(line) = tail.rest() {
+                        on_line(stream, line);
+                    }
+                }
+                return;
+            }
+        });
+        Self { stop, handle }
+    }
+
+    // Reads whatever the service wrote last and waits until it is delivered.
+    pub fn finish(self) {
+        let _ = self.stop.send(());
+        let _ = self.handle.join();
+    }
+}
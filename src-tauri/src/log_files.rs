@@ -0,0 +1,421 @@
+use crate::config_store::LogRotation;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+const FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+
+// Active service logs live at {root}/{id}.log; rotations are {id}.log.1 (newest)
+// up to {id}.log.N, gzipped as {id}.log.N.gz when the policy asks for it.
+// Each service gets a writer thread that owns the open file, so callers only
+// pay for a channel send.
+#[derive(Debug, Clone)]
+pub struct LogFiles {
+    root: PathBuf,
+    policies: Arc<Mutex<HashMap<String, LogRotation>>>,
+    writers: Arc<Mutex<HashMap<String, Sender<WriterCommand>>>>,
+}
+
+#[derive(Debug)]
+enum WriterCommand {
+    Line(String),
+    Flush(Sender<()>),
+    Clear(Sender<Result<(), String>>),
+}
+
+impl LogFiles {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            policies: Arc::new(Mutex::new(HashMap::new())),
+            writers: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn active_path(&self, id: &str) -> PathBuf {
+        self.root.join(format!("{id}.log"))
+    }
+
+    pub fn set_policy(&self, id: &str, policy: LogRotation) {
+        self.policies
+            .lock()
+            .expect("log policies lock")
+            .insert(id.to_string(), policy);
+    }
+
+    pub fn policy(&self, id: &str) -> LogRotation {
+        policy_for(&self.policies, id)
+    }
+
+    // Queues a line; it reaches disk on the next flush (at most FLUSH_INTERVAL).
+    pub fn append(&self, id: &str, line: &str) -> Result<(), String> {
+        self.send(id, WriterCommand::Line(line.to_string()))
+    }
+
+    // Blocks until every line queued so far for `id` is on disk.
+    pub fn flush(&self, id: &str) {
+        let (ack, done) = mpsc::channel();
+        if self.send(id, WriterCommand::Flush(ack)).is_ok() {
+            let _ = done.recv();
+        }
+    }
+
+    pub fn flush_all(&self) {
+        let ids: Vec<String> = self
+            .writers
+            .lock()
+            .expect("log writers lock")
+            .keys()
+            .cloned()
+            .collect();
+        for id in ids {
+            self.flush(&id);
+        }
+    }
+
+    // Truncates the active file and removes its rotations.
+    pub fn clear(&self, id: &str) -> Result<(), String> {
+        let (ack, done) = mpsc::channel();
+        self.send(id, WriterCommand::Clear(ack))?;
+        done.recv()
+            .map_err(|_| format!("log writer for {id} stopped"))?
+    }
+
+    pub fn clear_all(&self) -> Result<(), String> {
+        let mut ids: HashSet<String> = self.services().into_iter().collect();
+        ids.extend(
+            self.writers
+                .lock()
+                .expect("log writers lock")
+                .keys()
+                .cloned(),
+        );
+        for id in ids {
+            self.clear(&id)?;
+        }
+        Ok(())
+    }
+
+    pub fn prune_expired(&self) {
+        for id in self.services() {
+            prune_expired(&self.active_path(&id), &self.policy(&id));
+        }
+    }
+
+    // Deletes the oldest rotated service logs ({id}.log.N and {id}.log.N.gz)
+    // until everything under `logs_root` fits in `budget_bytes`. Other files
+    // count towards the budget but are never removed. Returns the number of
+    // bytes freed.
+    pub fn enforce_disk_budget(&self, logs_root: &Path, budget_bytes: u64) -> u64 {
+        let mut files = Vec::new();
+        collect_files(logs_root, &mut files);
+        let mut total: u64 = files.iter().map(|(_, len, _)| *len).sum();
+        if total <= budget_bytes {
+            return 0;
+        }
+        let mut candidates: Vec<_> = files
+            .into_iter()
+            .filter(|(path, _, _)| self.is_rotation(path))
+            .collect();
+        // Ties (same mtime) go to the higher rotation index, which is older.
+        candidates.sort_by(|a, b| a.2.cmp(&b.2).then_with(|| b.0.cmp(&a.0)));
+        let mut freed = 0;
+        for (path, len, _) in candidates {
+            if total <= budget_bytes {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total -= len;
+                freed += len;
+            }
+        }
+        freed
+    }
+
+    fn send(&self, id: &str, command: WriterCommand) -> Result<(), String> {
+        let mut writers = self.writers.lock().expect("log writers lock");
+        let sender = writers.entry(id.to_string()).or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel();
+            let writer = ServiceWriter::new(self.active_path(id));
+            let policies = Arc::clone(&self.policies);
+            let id = id.to_string();
+            thread::spawn(move || writer.run(&id, &policies, receiver));
+            sender
+        });
+        sender
+            .send(command)
+            .map_err(|_| format!("log writer for {id} stopped"))
+    }
+
+    fn is_rotation(&self, path: &Path) -> bool {
+        if path.parent() != Some(self.root.as_path()) {
+            return false;
+        }
+        let name = match path.file_name() {
+            Some(name) => name.to_string_lossy(),
+            None => return false,
+        };
+        let name = name.strip_suffix(".gz").unwrap_or(&name);
+        match name.rsplit_once('.') {
+            Some((stem, index)) => {
+                stem.ends_with(".log") && stem.len() > 4 && index.parse::<u32>().is_ok()
+            }
+            None => false,
+        }
+    }
+
+    fn services(&self) -> Vec<String> {
+        let mut services = Vec::new();
+        if let Ok(entries) = fs::read_dir(&self.root) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().and_then(|ext| ext.to_str()) == Some("log") {
+                    if let Some(stem) = path.file_stem() {
+                        services.push(stem.to_string_lossy().to_string());
+                    }
+                }
+            }
+        }
+        services.sort();
+        services
+    }
+}
+
+fn policy_for(policies: &Mutex<HashMap<String, LogRotation>>, id: &str) -> LogRotation {
+    policies
+        .lock()
+        .expect("log policies lock")
+        .get(id)
+        .cloned()
+        .unwrap_or_default()
+}
+
+struct ServiceWriter {
+    path: PathBuf,
+    // Opened lazily and dropped around rotation and clearing.
+    file: Option<BufWriter<fs::File>>,
+    size: u64,
+}
+
+impl ServiceWriter {
+    fn new(path: PathBuf) -> Self {
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        Self {
+            path,
+            file: None,
+            size,
+        }
+    }
+
+    fn run(
+        mut self,
+        id: &str,
+        policies: &Mutex<HashMap<String, LogRotation>>,
+        commands: mpsc::Receiver<WriterCommand>,
+    ) {
+        let mut last_flush = Instant::now();
+        loop {
+            match commands.recv_timeout(FLUSH_INTERVAL) {
+                Ok(WriterCommand::Line(line)) => {
+                    let policy = policy_for(policies, id);
+                    let _ = self.write(&line, &policy);
+                }
+                Ok(WriterCommand::Flush(ack)) => {
+                    self.flush();
+                    let _ = ack.send(());
+                }
+                Ok(WriterCommand::Clear(ack)) => {
+                    let _ = ack.send(self.clear());
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.flush();
+                    return;
+                }
+            }
+            if last_flush.elapsed() >= FLUSH_INTERVAL {
+                self.flush();
+                last_flush = Instant::now();
+            }
+        }
+    }
+
+    fn write(&mut self, line: &str, policy: &LogRotation) -> Result<(), String> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > policy.max_size_bytes {
+            self.flush();
+            self.file = None;
+            // Readers keep their handles: rotation only renames files.
+            rotate(&self.path, policy)?;
+            self.size = 0;
+        }
+        if self.file.is_none() {
+            if let Some(dir) = self.path.parent() {
+                fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+            let file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .map_err(|e| e.to_string())?;
+            self.size = file.metadata().map(|m| m.len()).unwrap_or(0);
+            self.file = Some(BufWriter::new(file));
+        }
+        let file = self.file.as_mut().expect("log file open");
+        writeln!(file, "{line}").map_err(|e| e.to_string())?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn flush(&mut self) {
+        if let Some(file) = self.file.as_mut() {
+            let _ = file.flush();
+        }
+    }
+
+    fn clear(&mut self) -> Result<(), String> {
+        self.flush();
+        self.file = None;
+        if self.path.exists() {
+            fs::write(&self.path, "").map_err(|e| e.to_string())?;
+        }
+        remove_rotations(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+// Rotations of `path`, oldest first.
+pub fn rotated_files(path: &Path) -> Vec<PathBuf> {
+    let mut files = indexed_rotations(path);
+    files.sort_by_key(|(index, _)| std::cmp::Reverse(*index));
+    files.into_iter().map(|(_, path)| path).collect()
+}
+
+pub fn read_log(path: &Path) -> Result<String, String> {
+    let mut raw = String::new();
+    if path.extension().and_then(|ext| ext.to_str()) == Some("gz") {
+        let file = fs::File::open(path).map_err(|e| e.to_string())?;
+        GzDecoder::new(file)
+            .read_to_string(&mut raw)
+            .map_err(|e| e.to_string())?;
+    } else {
+        raw = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    }
+    Ok(raw)
+}
+
+pub fn rotate(path: &Path, policy: &LogRotation) -> Result<(), String> {
+    let mut existing = indexed_rotations(path);
+    existing.sort_by_key(|(index, _)| std::cmp::Reverse(*index));
+    for (index, rotated) in existing {
+        if index >= policy.max_files {
+            fs::remove_file(&rotated).map_err(|e| e.to_string())?;
+            continue;
+        }
+        let gz = rotated.extension().and_then(|ext| ext.to_str()) == Some("gz");
+        fs::rename(&rotated, rotation_path(path, index + 1, gz)).map_err(|e| e.to_string())?;
+    }
+    if policy.max_files == 0 {
+        return fs::remove_file(path).map_err(|e| e.to_string());
+    }
+    let first = rotation_path(path, 1, false);
+    fs::rename(path, &first).map_err(|e| e.to_string())?;
+    if policy.compress {
+        compress(&first, &rotation_path(path, 1, true))?;
+    }
+    prune_expired(path, policy);
+    Ok(())
+}
+
+pub fn prune_expired(path: &Path, policy: &LogRotation) {
+    if policy.max_age_days == 0 {
+        return;
+    }
+    let max_age = Duration::from_secs(u64::from(policy.max_age_days) * 86_400);
+    for rotated in rotated_files(path) {
+        let expired = fs::metadata(&rotated)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > max_age);
+        if expired {
+            let _ = fs::remove_file(&rotated);
+        }
+    }
+}
+
+pub fn remove_rotations(path: &Path) -> Result<(), String> {
+    for rotated in rotated_files(path) {
+        fs::remove_file(&rotated).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn rotation_path(path: &Path, index: u32, gz: bool) -> PathBuf {
+    let suffix = if gz { ".gz" } else { "" };
+    PathBuf::from(format!("{}.{index}{suffix}", path.display()))
+}
+
+fn indexed_rotations(path: &Path) -> Vec<(u32, PathBuf)> {
+    let (dir, name) = match (path.parent(), path.file_name()) {
+        (Some(dir), Some(name)) => (dir, name.to_string_lossy().to_string()),
+        _ => return Vec::new(),
+    };
+    let prefix = format!("{name}.");
+    let mut files = Vec::new();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let index = file_name
+                .strip_prefix(&prefix)
+                .map(|rest| rest.strip_suffix(".gz").unwrap_or(rest))
+                .and_then(|index| index.parse::<u32>().ok());
+            if let Some(index) = index {
+                files.push((index, entry.path()));
+            }
+        }
+    }
+    files
+}
+
+fn compress(src: &Path, dst: &Path) -> Result<(), String> {
+    let raw = fs::read(src).map_err(|e| e.to_string())?;
+    let file = fs::File::create(dst).map_err(|e| e.to_string())?;
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    encoder.write_all(&raw).map_err(|e| e.to_string())?;
+    encoder.finish().map_err(|e| e.to_string())?;
+    fs::remove_file(src).map_err(|e| e.to_string())
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<(PathBuf, u64, SystemTime)>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+        if metadata.is_dir() {
+            collect_files(&path, files);
+        } else {
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            files.push((path, metadata.len(), modified));
+        }
+    }
+}
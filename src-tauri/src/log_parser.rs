@@ -0,0 +1,233 @@
+use serde_json::Value;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct ParsedLine {
+    pub level: String,
+    pub message: String,
+    pub fields: HashMap<String, String>,
+}
+
+impl ParsedLine {
+    fn plain(level: &str, line: &str) -> Self {
+        Self {
+            level: level.to_string(),
+            message: line.to_string(),
+            fields: HashMap::new(),
+        }
+    }
+}
+
+// `stream_level` is what the line would get without a parser ("info" for
+// stdout, "error" for stderr) and is used whenever a line is not recognised.
+// The service's own timestamp, when present, is kept in `fields["time"]`.
+pub fn parse_line(format: Option<&str>, stream_level: &str, line: &str) -> ParsedLine {
+    let parsed = match format.unwrap_or("plain") {
+        "postgres" => parse_postgres(line),
+        "mariadb" => parse_mariadb(line),
+        "php-server" => parse_php_server(line),
+        "mailpit" => parse_json(line).or_else(|| parse_logfmt(line)),
+        "json" => parse_json(line),
+        _ => None,
+    };
+    parsed.unwrap_or_else(|| ParsedLine::plain(stream_level, line))
+}
+
+pub fn normalize_level(raw: &str) -> Option<&'static str> {
+    let lower = raw.trim().to_ascii_lowercase();
+    let level = match lower.as_str() {
+        "trace" | "debug" | "debug1" | "debug2" | "debug3" | "debug4" | "debug5" => "debug",
+        "info" | "information" | "notice" | "note" | "log" | "statement" | "detail" | "hint"
+        | "context" | "system" => "info",
+        "warn" | "warning" => "warn",
+        "error" | "err" | "fatal" | "panic" | "critical" | "crit" | "alert" | "emerg" => "error",
+        _ => return None,
+    };
+    Some(level)
+}
+
+// Default log_line_prefix '%m [%p] ':
+// "2024-05-01 10:00:00.123 UTC [4242] LOG:  database system is ready"
+// With log_error_verbosity=verbose the SQLSTATE follows the severity:
+// "... ERROR:  42P01: relation \"users\" does not exist"
+fn parse_postgres(line: &str) -> Option<ParsedLine> {
+    let open = line.find(" [")?;
+    let close = open + line[open..].find("] ")?;
+    let time = line[..open].trim();
+    let pid = &line[open + 2..close];
+    if time.is_empty() || !pid.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let rest = &line[close + 2..];
+    let (severity, message) = rest.split_once(':')?;
+    let level = normalize_level(severity)?;
+    let mut message = message.trim_start().to_string();
+
+    let mut fields = HashMap::new();
+    fields.insert("time".to_string(), time.to_string());
+    fields.insert("pid".to_string(), pid.to_string());
+    fields.insert("severity".to_string(), severity.to_string());
+    if let Some((code, tail)) = message.split_once(": ") {
+        if code.len() == 5 && code.chars().all(|c| c.is_ascii_digit() || c.is_ascii_uppercase()) {
+            fields.insert("sqlState".to_string(), code.to_string());
+            message = tail.to_string();
+        }
+    }
+    Some(ParsedLine {
+        level: level.to_string(),
+        message,
+        fields,
+    })
+}
+
+// "2024-05-01 10:00:00 0 [Note] InnoDB: Buffer pool(s) load completed"
+fn parse_mariadb(line: &str) -> Option<ParsedLine> {
+    let open = line.find(" [")?;
+    let close = open + line[open..].find("] ")?;
+    let severity = &line[open + 2..close];
+    let level = normalize_level(severity)?;
+    let head: Vec<&str> = line[..open].split_whitespace().collect();
+    if head.len() < 2 {
+        return None;
+    }
+    let mut fields = HashMap::new();
+    fields.insert("time".to_string(), format!("{} {}", head[0], head[1]));
+    if let Some(thread) = head.get(2) {
+        fields.insert("thread".to_string(), thread.to_string());
+    }
+    fields.insert("severity".to_string(), severity.to_string());
+    let message = line[close + 2..].to_string();
+    if let Some((subsystem, _)) = message.split_once(": ") {
+        if !subsystem.contains(' ') {
+            fields.insert("subsystem".to_string(), subsystem.to_string());
+        }
+    }
+    Some(ParsedLine {
+        level: level.to_string(),
+        message,
+        fields,
+    })
+}
+
+// "[Wed May  1 10:00:00 2024] 127.0.0.1:51234 [404]: GET /missing - No such file"
+// "[Wed May  1 10:00:00 2024] 127.0.0.1:51234 Accepted"
+// "PHP Warning:  Undefined variable $x in /app/index.php on line 3"
+fn parse_php_server(line: &str) -> Option<ParsedLine> {
+    if let Some(rest) = line.strip_prefix("PHP ") {
+        let (severity, message) = rest.split_once(':')?;
+        let level = if severity.contains("Fatal") || severity.contains("Parse") {
+            "error"
+        } else if severity.contains("Warning") || severity.contains("Deprecated") {
+            "warn"
+        } else {
+            "info"
+        };
+        let mut fields = HashMap::new();
+        fields.insert("severity".to_string(), severity.to_string());
+        return Some(ParsedLine {
+            level: level.to_string(),
+            message: message.trim().to_string(),
+            fields,
+        });
+    }
+
+    let rest = line.strip_prefix('[')?;
+    let (time, rest) = rest.split_once("] ")?;
+    let mut fields = HashMap::new();
+    fields.insert("time".to_string(), time.to_string());
+    let (remote, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+    if remote.contains(':') {
+        fields.insert("remote".to_string(), remote.to_string());
+    }
+
+    let request = rest
+        .strip_prefix('[')
+        .and_then(|tail| tail.split_once("]: "));
+    let (status, request) = match request {
+        Some((status, request)) => (status, request),
+        None => {
+            return Some(ParsedLine {
+                level: "info".to_string(),
+                message: rest.to_string(),
+                fields,
+            })
+        }
+    };
+    let code = status.parse::<u16>().ok()?;
+    let mut parts = request.splitn(3, ' ');
+    if let (Some(method), Some(path)) = (parts.next(), parts.next()) {
+        fields.insert("method".to_string(), method.to_string());
+        fields.insert("path".to_string(), path.to_string());
+    }
+    fields.insert("status".to_string(), status.to_string());
+    let level = match code {
+        500..=599 => "error",
+        400..=499 => "warn",
+        _ => "info",
+    };
+    Some(ParsedLine {
+        level: level.to_string(),
+        message: request.to_string(),
+        fields,
+    })
+}
+
+// {"level":"info","time":"2024-05-01T10:00:00Z","msg":"listening","port":8025}
+fn parse_json(line: &str) -> Option<ParsedLine> {
+    let value: Value = serde_json::from_str(line.trim()).ok()?;
+    let object = value.as_object()?;
+    let mut fields = HashMap::new();
+    let mut level = None;
+    let mut message = None;
+    for (key, value) in object {
+        let text = match value {
+            Value::String(text) => text.clone(),
+            Value::Null => continue,
+            other => other.to_string(),
+        };
+        match key.as_str() {
+            "level" | "lvl" | "severity" => level = normalize_level(&text),
+            "msg" | "message" => message = Some(text),
+            "time" | "ts" | "timestamp" | "@timestamp" => {
+                fields.insert("time".to_string(), text);
+            }
+            _ => {
+                fields.insert(key.clone(), text);
+            }
+        }
+    }
+    Some(ParsedLine {
+        level: level.unwrap_or("info").to_string(),
+        message: message.unwrap_or_else(|| line.to_string()),
+        fields,
+    })
+}
+
+// time="2024/05/01 10:00:00" level=info msg="[smtpd] starting on [::]:1025"
+fn parse_logfmt(line: &str) -> Option<ParsedLine> {
+    let mut pairs = HashMap::new();
+    let mut rest = line.trim();
+    while !rest.is_empty() {
+        let (key, tail) = rest.split_once('=')?;
+        if key.is_empty() || key.contains(' ') {
+            return None;
+        }
+        let (value, tail) = match tail.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"')?;
+                (&quoted[..end], &quoted[end + 1..])
+            }
+            None => tail.split_once(' ').unwrap_or((tail, "")),
+        };
+        pairs.insert(key.to_string(), value.to_string());
+        rest = tail.trim_start();
+    }
+    let level = normalize_level(pairs.get("level")?)?;
+    let message = pairs.remove("msg").unwrap_or_default();
+    pairs.remove("level");
+    Some(ParsedLine {
+        level: level.to_string(),
+        message,
+        fields: pairs,
+    })
+}
@@ -0,0 +1,387 @@
+use crate::log_files;
+use crate::models::LogEntry;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogQuery {
+    // Empty means every service with logs in memory or on disk.
+    #[serde(default)]
+    pub services: Vec<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+    // Treat `text` as a regular expression instead of a substring.
+    #[serde(default)]
+    pub regex: bool,
+    // Inclusive bounds; Unix seconds or RFC 3339.
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(rename = "minLevel")]
+    #[serde(default)]
+    pub min_level: Option<String>,
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub limit: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogPage {
+    // Newest first.
+    pub entries: Vec<LogEntry>,
+    #[serde(rename = "nextCursor")]
+    pub next_cursor: Option<String>,
+}
+
+pub fn query(
+    log_root: &Path,
+    memory: HashMap<String, Vec<LogEntry>>,
+    query: &LogQuery,
+) -> Result<LogPage, String> {
+    let matcher = Matcher::new(query)?;
+    let services = services_for(log_root, &memory, &query.services);
+    let limit = if query.limit == 0 { 200 } else { query.limit };
+    let cursor = query.cursor.as_deref().map(parse_cursor).transpose()?;
+
+    // A page needs the entries the cursor skips at its timestamp, `limit`
+    // more and one to tell whether there is a next page.
+    let needed = cursor.map_or(0, |(_, skip)| skip) + limit + 1;
+
+    let mut matched: Vec<(i64, LogEntry)> = Vec::new();
+    for service in &services {
+        let in_memory = memory.get(service).cloned().unwrap_or_default();
+        // The buffer mirrors the tail of the active file but carries parsed
+        // fields, so prefer it and drop the duplicate lines read from disk.
+        let mut seen: HashMap<(String, String, String), usize> = HashMap::new();
+        for entry in &in_memory {
+            *seen.entry(entry_key(entry)).or_default() += 1;
+        }
+        // Sources are walked newest first and each one only holds entries
+        // older than the previous, so once enough entries older than the
+        // cursor have matched the remaining files cannot change the page.
+        let mut eligible = Vec::new();
+        let mut buffer = Some(in_memory);
+        let mut files = service_log_files(log_root, service).into_iter();
+        loop {
+            let (entries, from_disk) = match buffer.take() {
+                Some(entries) => (entries, false),
+                None => match files.next() {
+                    Some(path) => (read_log_file(&path, service), true),
+                    None => break,
+                },
+            };
+            let mut earliest: Option<i64> = None;
+            for entry in entries.into_iter().rev() {
+                if from_disk {
+                    if let Some(count) = seen.get_mut(&entry_key(&entry)) {
+                        if *count > 0 {
+                            *count -= 1;
+                            continue;
+                        }
+                    }
+                }
+                let ts = ts_millis(&entry.ts).unwrap_or(0);
+                earliest = Some(earliest.map_or(ts, |earliest| earliest.min(ts)));
+                if !matcher.matches(&entry, ts) {
+                    continue;
+                }
+                if cursor.is_none_or(|(cursor_ts, _)| ts <= cursor_ts) {
+                    eligible.push(ts);
+                }
+                matched.push((ts, entry));
+            }
+            if let Some(earliest) = earliest {
+                if matcher.from.is_some_and(|from| earliest < from)
+                    || filled(&mut eligible, needed, earliest)
+                {
+                    break;
+                }
+            }
+        }
+    }
+    // Entries were collected newest first and the sort is stable, so entries
+    // sharing a timestamp stay newest first as well.
+    matched.sort_by_key(|(ts, _)| std::cmp::Reverse(*ts));
+
+    let mut entries = Vec::new();
+    let mut skipped_at_cursor = 0usize;
+    let mut last: Option<(i64, usize)> = None;
+    for (ts, entry) in matched {
+        if let Some((cursor_ts, skip)) = cursor {
+            if ts > cursor_ts {
+                continue;
+            }
+            if ts == cursor_ts && skipped_at_cursor < skip {
+                skipped_at_cursor += 1;
+                continue;
+            }
+        }
+        if entries.len() == limit {
+            let (last_ts, same) = last.unwrap_or((ts, 0));
+            return Ok(LogPage {
+                entries,
+                next_cursor: Some(format!("{last_ts}:{same}")),
+            });
+        }
+        // Count how many returned entries share the last timestamp so the
+        // next page can skip exactly those.
+        last = Some(match last {
+            Some((last_ts, same)) if last_ts == ts => (ts, same + 1),
+            _ => {
+                let base = match cursor {
+                    Some((cursor_ts, skip)) if cursor_ts == ts => skip,
+                    _ => 0,
+                };
+                (ts, base + 1)
+            }
+        });
+        entries.push(entry);
+    }
+    Ok(LogPage {
+        entries,
+        next_cursor: None,
+    })
+}
+
+// Whether `needed` eligible entries are all newer than `earliest`, so that
+// older sources cannot add to the page.
+fn filled(eligible: &mut [i64], needed: usize, earliest: i64) -> bool {
+    if eligible.len() < needed {
+        return false;
+    }
+    eligible.sort_unstable_by(|a, b| b.cmp(a));
+    earliest < eligible[needed - 1]
+}
+
+pub fn level_rank(level: &str) -> u8 {
+    match level {
+        "debug" => 0,
+        "info" => 1,
+        "warn" => 2,
+        "error" => 3,
+        _ => 1,
+    }
+}
+
+// Accepts Unix seconds ("1714557600") or RFC 3339 with optional fraction
+// ("2024-05-01T10:00:00.123Z", "2024-05-01T12:00:00+02:00").
+pub fn ts_millis(ts: &str) -> Option<i64> {
+    let ts = ts.trim();
+    if let Ok(secs) = ts.parse::<i64>() {
+        return Some(secs * 1000);
+    }
+    if ts.len() < 19 {
+        return None;
+    }
+    let number = |range: std::ops::Range<usize>| ts.get(range).and_then(|s| s.parse::<i64>().ok());
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+    let mut rest = &ts[19..];
+    let mut millis = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits: String = fraction
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        rest = &fraction[digits.len()..];
+        let padded = format!("{:0<3}", &digits[..digits.len().min(3)]);
+        millis = padded.parse::<i64>().ok()?;
+    }
+    let offset_secs = match rest {
+        "" | "Z" | "z" => 0,
+        _ => {
+            let sign = if rest.starts_with('-') { -1 } else { 1 };
+            let hours = rest.get(1..3)?.parse::<i64>().ok()?;
+            let minutes = rest.get(4..6)?.parse::<i64>().ok()?;
+            sign * (hours * 3600 + minutes * 60)
+        }
+    };
+    let days = days_from_civil(year, month, day);
+    let secs = days * 86_400 + hour * 3600 + minute * 60 + second - offset_secs;
+    Some(secs * 1000 + millis)
+}
+
+// Inverse of `ts_millis` for UTC: "2024-05-01T10:00:00.250Z".
+pub fn format_millis(ms: i64) -> String {
+    let secs = ms.div_euclid(1000);
+    let millis = ms.rem_euclid(1000);
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    let rem = secs.rem_euclid(86_400);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{millis:03}Z",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+pub fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+// Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let yoe = year - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+struct Matcher {
+    text: Option<String>,
+    regex: Option<Regex>,
+    from: Option<i64>,
+    to: Option<i64>,
+    min_rank: u8,
+}
+
+impl Matcher {
+    fn new(query: &LogQuery) -> Result<Self, String> {
+        let bound = |value: &Option<String>, name: &str| -> Result<Option<i64>, String> {
+            match value {
+                Some(raw) => ts_millis(raw)
+                    .map(Some)
+                    .ok_or_else(|| format!("invalid {name} timestamp: {raw}")),
+                None => Ok(None),
+            }
+        };
+        let regex = match (&query.text, query.regex) {
+            (Some(pattern), true) => {
+                Some(Regex::new(pattern).map_err(|e| format!("invalid regex: {e}"))?)
+            }
+            _ => None,
+        };
+        Ok(Self {
+            text: if query.regex {
+                None
+            } else {
+                query.text.clone().filter(|t| !t.is_empty())
+            },
+            regex,
+            from: bound(&query.from, "from")?,
+            to: bound(&query.to, "to")?,
+            min_rank: query.min_level.as_deref().map(level_rank).unwrap_or(0),
+        })
+    }
+
+    fn matches(&self, entry: &LogEntry, ts: i64) -> bool {
+        if level_rank(&entry.level) < self.min_rank {
+            return false;
+        }
+        if self.from.is_some_and(|from| ts < from) || self.to.is_some_and(|to| ts > to) {
+            return false;
+        }
+        if let Some(text) = &self.text {
+            if !entry.message.contains(text.as_str()) {
+                return false;
+            }
+        }
+        if let Some(regex) = &self.regex {
+            if !regex.is_match(&entry.message) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn services_for(
+    log_root: &Path,
+    memory: &HashMap<String, Vec<LogEntry>>,
+    requested: &[String],
+) -> Vec<String> {
+    if !requested.is_empty() {
+        return requested.to_vec();
+    }
+    let mut services: HashSet<String> = memory.keys().cloned().collect();
+    if let Ok(entries) = std::fs::read_dir(log_root) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) == Some("log") {
+                if let Some(stem) = path.file_stem() {
+                    services.insert(stem.to_string_lossy().to_string());
+                }
+            }
+        }
+    }
+    let mut services: Vec<String> = services.into_iter().collect();
+    services.sort();
+    services
+}
+
+// Newest first: {id}.log, then rotations (plain or gzipped).
+fn service_log_files(log_root: &Path, service: &str) -> Vec<PathBuf> {
+    let active = log_root.join(format!("{service}.log"));
+    let mut files = log_files::rotated_files(&active);
+    if active.exists() {
+        files.push(active);
+    }
+    files.reverse();
+    files
+}
+
+// Lines are written as "{ts} [{level}] {message}".
+fn read_log_file(path: &Path, service: &str) -> Vec<LogEntry> {
+    let raw = match log_files::read_log(path) {
+        Ok(raw) => raw,
+        Err(_) => return Vec::new(),
+    };
+    raw.lines()
+        .filter_map(|line| {
+            let (ts, rest) = line.split_once(' ')?;
+            let rest = rest.strip_prefix('[')?;
+            let (level, message) = rest
+                .split_once("] ")
+                .unwrap_or((rest.trim_end_matches(']'), ""));
+            Some(LogEntry {
+                ts: ts.to_string(),
+                seq: 0,
+                level: level.to_string(),
+                service: service.to_string(),
+                message: message.to_string(),
+                fields: HashMap::new(),
+            })
+        })
+        .collect()
+}
+
+fn entry_key(entry: &LogEntry) -> (String, String, String) {
+    (entry.ts.clone(), entry.level.clone(), entry.message.clone())
+}
+
+fn parse_cursor(cursor: &str) -> Result<(i64, usize), String> {
+    let (ts, skip) = cursor
+        .split_once(':')
+        .ok_or_else(|| "invalid cursor".to_string())?;
+    let ts = ts
+        .parse::<i64>()
+        .map_err(|_| "invalid cursor".to_string())?;
+    let skip = skip
+        .parse::<usize>()
+        .map_err(|_| "invalid cursor".to_string())?;
+    Ok((ts, skip))
+}
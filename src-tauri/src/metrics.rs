@@ -1,7 +1,8 @@
 use serde::Serialize;
-use std::sync::OnceLock;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
 use std::time::{SystemTime, UNIX_EPOCH};
-use sysinfo::{CpuRefreshKind, MemoryRefreshKind, RefreshKind, System};
+use sysinfo::{CpuRefreshKind, MemoryRefreshKind, Pid, RefreshKind, System};
 
 use crate::config_store::ConfigStore;
 
@@ -18,7 +19,28 @@ pub struct MetricsSnapshot {
     pub mem_mb: u64,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceMetrics {
+    pub service: String,
+    pub pids: Vec<u32>,
+    #[serde(rename = "cpuPercent")]
+    pub cpu_percent: f32,
+    #[serde(rename = "rssBytes")]
+    pub rss_bytes: u64,
+    #[serde(rename = "diskReadBytes")]
+    pub disk_read_bytes: u64,
+    #[serde(rename = "diskWriteBytes")]
+    pub disk_write_bytes: u64,
+    // Only Linux exposes per-process thread lists through sysinfo.
+    pub threads: Option<usize>,
+    #[serde(rename = "listeningPorts")]
+    pub listening_ports: Vec<u16>,
+}
+
 static START_TS: OnceLock<u64> = OnceLock::new();
+// Kept between calls so CPU usage is measured over the interval since the
+// previous sample instead of requiring a sleep on every call.
+static PROCESS_SAMPLER: OnceLock<Mutex<System>> = OnceLock::new();
 
 pub fn init_start() {
     let _ = START_TS.set(now_secs());
@@ -52,6 +74,194 @@ pub fn snapshot(config: &ConfigStore) -> Result<MetricsSnapshot, String> {
     })
 }
 
+// `roots` are (service id, pid) pairs; every metric includes the root's
+// descendants so `npm run dev` or postgres backends are counted too.
+pub fn service_metrics(roots: &[(String, u32)]) -> Vec<ServiceMetrics> {
+    let sampler = PROCESS_SAMPLER.get_or_init(|| {
+        let mut system = System::new();
+        system.refresh_processes();
+        std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        Mutex::new(system)
+    });
+    let mut system = sampler.lock().expect("process sampler lock");
+    system.refresh_processes();
+
+    let mut children: HashMap<Pid, Vec<Pid>> = HashMap::new();
+    for (pid, process) in system.processes() {
+        if process.thread_kind().is_some() {
+            continue;
+        }
+        if let Some(parent) = process.parent() {
+            children.entry(parent).or_default().push(*pid);
+        }
+    }
+
+    let trees: Vec<(String, Vec<Pid>)> = roots
+        .iter()
+        .map(|(service, root)| {
+            let root = Pid::from_u32(*root);
+            let mut tree = Vec::new();
+            let mut queue = vec![root];
+            while let Some(pid) = queue.pop() {
+                if system.process(pid).is_none() {
+                    continue;
+                }
+                tree.push(pid);
+                queue.extend(children.get(&pid).into_iter().flatten().copied());
+            }
+            (service.clone(), tree)
+        })
+        .collect();
+    let all_pids: Vec<u32> = trees
+        .iter()
+        .flat_map(|(_, tree)| tree.iter().map(|pid| pid.as_u32()))
+        .collect();
+    let ports = listening_ports(&all_pids);
+
+    trees
+        .into_iter()
+        .map(|(service, tree)| {
+            let mut metrics = ServiceMetrics {
+                service,
+                pids: tree.iter().map(|pid| pid.as_u32()).collect(),
+                cpu_percent: 0.0,
+                rss_bytes: 0,
+                disk_read_bytes: 0,
+                disk_write_bytes: 0,
+                threads: None,
+                listening_ports: Vec::new(),
+            };
+            let mut listening = HashSet::new();
+            for pid in &tree {
+                if let Some(process) = system.process(*pid) {
+                    let disk = process.disk_usage();
+                    metrics.cpu_percent += process.cpu_usage();
+                    metrics.rss_bytes += process.memory();
+                    metrics.disk_read_bytes += disk.total_read_bytes;
+                    metrics.disk_write_bytes += disk.total_written_bytes;
+                    if let Some(tasks) = process.tasks() {
+                        *metrics.threads.get_or_insert(0) += tasks.len().max(1);
+                    }
+                }
+                if let Some(found) = ports.get(&pid.as_u32()) {
+                    listening.extend(found.iter().copied());
+                }
+            }
+            metrics.listening_ports = listening.into_iter().collect();
+            metrics.listening_ports.sort_unstable();
+            metrics
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn listening_ports(pids: &[u32]) -> HashMap<u32, Vec<u16>> {
+    // /proc/net/tcp{,6} maps listening sockets to inodes; /proc/<pid>/fd maps
+    // inodes back to the processes holding them.
+    let mut inodes: HashMap<String, u16> = HashMap::new();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        let raw = match std::fs::read_to_string(table) {
+            Ok(raw) => raw,
+            Err(_) => continue,
+        };
+        for line in raw.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || fields[3] != "0A" {
+                continue;
+            }
+            let port = fields[1]
+                .rsplit(':')
+                .next()
+                .and_then(|hex| u16::from_str_radix(hex, 16).ok());
+            if let Some(port) = port {
+                inodes.insert(fields[9].to_string(), port);
+            }
+        }
+    }
+
+    let mut ports = HashMap::new();
+    for pid in pids {
+        let entries = match std::fs::read_dir(format!("/proc/{pid}/fd")) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        let mut found = Vec::new();
+        for entry in entries.flatten() {
+            let target = match std::fs::read_link(entry.path()) {
+                Ok(target) => target.to_string_lossy().to_string(),
+                Err(_) => continue,
+            };
+            let inode = target
+                .strip_prefix("socket:[")
+                .and_then(|rest| rest.strip_suffix(']'));
+            if let Some(port) = inode.and_then(|inode| inodes.get(inode)) {
+                found.push(*port);
+            }
+        }
+        if !found.is_empty() {
+            ports.insert(*pid, found);
+        }
+    }
+    ports
+}
+
+#[cfg(target_os = "macos")]
+fn listening_ports(pids: &[u32]) -> HashMap<u32, Vec<u16>> {
+    let mut ports: HashMap<u32, Vec<u16>> = HashMap::new();
+    if pids.is_empty() {
+        return ports;
+    }
+    let pid_list = pids.iter().map(|pid| pid.to_string()).collect::<Vec<_>>().join(",");
+    let output = match std::process::Command::new("lsof")
+        .args(["-nP", "-a", "-iTCP", "-sTCP:LISTEN", "-Fn", "-p", &pid_list])
+        .output()
+    {
+        Ok(output) => output,
+        Err(_) => return ports,
+    };
+    // -F output: a "p<pid>" line followed by "n<addr>:<port>" lines.
+    let mut current = None;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(pid) = line.strip_prefix('p') {
+            current = pid.parse::<u32>().ok();
+        } else if let (Some(addr), Some(pid)) = (line.strip_prefix('n'), current) {
+            if let Some(port) = addr.rsplit(':').next().and_then(|p| p.parse::<u16>().ok()) {
+                ports.entry(pid).or_default().push(port);
+            }
+        }
+    }
+    ports
+}
+
+#[cfg(target_os = "windows")]
+fn listening_ports(pids: &[u32]) -> HashMap<u32, Vec<u16>> {
+    let mut ports: HashMap<u32, Vec<u16>> = HashMap::new();
+    let output = match std::process::Command::new("netstat").args(["-ano", "-p", "TCP"]).output() {
+        Ok(output) => output,
+        Err(_) => return ports,
+    };
+    // "  TCP    0.0.0.0:5432    0.0.0.0:0    LISTENING    1234"
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() != 5 || fields[3] != "LISTENING" {
+            continue;
+        }
+        let pid = match fields[4].parse::<u32>() {
+            Ok(pid) if pids.contains(&pid) => pid,
+            _ => continue,
+        };
+        if let Some(port) = fields[1].rsplit(':').next().and_then(|p| p.parse::<u16>().ok()) {
+            ports.entry(pid).or_default().push(port);
+        }
+    }
+    ports
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn listening_ports(_pids: &[u32]) -> HashMap<u32, Vec<u16>> {
+    HashMap::new()
+}
+
 fn now_secs() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -12,19 +12,221 @@ pub struct PortDef {
 pub struct HealthCheck {
     #[serde(rename = "type")]
     pub kind: String,
+    // Unused by "pid" and "exec".
+    #[serde(default)]
     pub target: String,
     #[serde(rename = "timeoutMs")]
     pub timeout_ms: u64,
     #[serde(rename = "intervalMs")]
     pub interval_ms: u64,
+    #[serde(default)]
+    pub http: Option<HttpProbe>,
+    // "exec" only: program and args, run with the runtime's scoped PATH;
+    // exit code 0 is healthy.
+    #[serde(default)]
+    pub command: Vec<String>,
 }
 
+// Without an explicit check a service counts as healthy while its process runs.
+impl Default for HealthCheck {
+    fn default() -> Self {
+        Self {
+            kind: "pid".to_string(),
+            target: String::new(),
+            timeout_ms: 3000,
+            interval_ms: 2000,
+            http: None,
+            command: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpProbe {
+    #[serde(rename = "expectedStatus")]
+    #[serde(default)]
+    pub expected_status: StatusRange,
+    #[serde(rename = "bodyContains")]
+    #[serde(default)]
+    pub body_contains: Option<String>,
+    #[serde(rename = "bodyRegex")]
+    #[serde(default)]
+    pub body_regex: Option<String>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(rename = "tlsVerify")]
+    #[serde(default = "default_tls_verify")]
+    pub tls_verify: bool,
+}
+
+impl Default for HttpProbe {
+    fn default() -> Self {
+        Self {
+            expected_status: StatusRange::default(),
+            body_contains: None,
+            body_regex: None,
+            headers: HashMap::new(),
+            tls_verify: default_tls_verify(),
+        }
+    }
+}
+
+fn default_tls_verify() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusRange {
+    pub from: u16,
+    pub to: u16,
+}
+
+impl Default for StatusRange {
+    fn default() -> Self {
+        Self { from: 200, to: 399 }
+    }
+}
+
+// Up to `max_retries` restarts per `window_ms`; one more crash inside the
+// window opens the circuit and leaves the service in "crash-loop".
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RestartPolicy {
     #[serde(rename = "maxRetries")]
     pub max_retries: u32,
+    // First delay; doubled per crash in the window, capped at max_backoff_ms.
     #[serde(rename = "backoffMs")]
     pub backoff_ms: u64,
+    #[serde(rename = "maxBackoffMs")]
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+    #[serde(rename = "windowMs")]
+    #[serde(default = "default_restart_window_ms")]
+    pub window_ms: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            backoff_ms: 2000,
+            max_backoff_ms: default_max_backoff_ms(),
+            window_ms: default_restart_window_ms(),
+        }
+    }
+}
+
+fn default_max_backoff_ms() -> u64 {
+    60_000
+}
+
+fn default_restart_window_ms() -> u64 {
+    300_000
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StopPolicy {
+    #[serde(default = "default_stop_signal")]
+    pub signal: String,
+    #[serde(default)]
+    pub command: Vec<String>,
+    #[serde(rename = "timeoutMs")]
+    #[serde(default = "default_stop_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl Default for StopPolicy {
+    fn default() -> Self {
+        Self {
+            signal: default_stop_signal(),
+            command: Vec::new(),
+            timeout_ms: default_stop_timeout_ms(),
+        }
+    }
+}
+
+// How a running service picks up changed config files. The command is tried
+// first, then the signal; with neither, a config change needs a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReloadPolicy {
+    #[serde(default)]
+    pub signal: Option<String>,
+    #[serde(default)]
+    pub command: Vec<String>,
+    #[serde(rename = "timeoutMs")]
+    #[serde(default = "default_reload_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl Default for ReloadPolicy {
+    fn default() -> Self {
+        Self {
+            signal: None,
+            command: Vec::new(),
+            timeout_ms: default_reload_timeout_ms(),
+        }
+    }
+}
+
+fn default_reload_timeout_ms() -> u64 {
+    10_000
+}
+
+// A command run around a service's lifecycle with the service's cwd, env and
+// PATH. Its output is captured into the service log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hook {
+    pub command: Vec<String>,
+    #[serde(rename = "timeoutMs")]
+    #[serde(default = "default_hook_timeout_ms")]
+    pub timeout_ms: u64,
+    // "abort" skips the remaining hooks of the phase and, before or after a
+    // start, fails the start; "warn" only logs the failure.
+    #[serde(rename = "onFailure")]
+    #[serde(default = "default_hook_failure")]
+    pub on_failure: String,
+}
+
+fn default_hook_timeout_ms() -> u64 {
+    60_000
+}
+
+fn default_hook_failure() -> String {
+    "abort".to_string()
+}
+
+// preStart runs before the process is spawned, postStart once it is healthy
+// (dependents wait for it), preStop while it is still running and postStop
+// after it exited.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LifecycleHooks {
+    #[serde(rename = "preStart")]
+    #[serde(default)]
+    pub pre_start: Vec<Hook>,
+    #[serde(rename = "postStart")]
+    #[serde(default)]
+    pub post_start: Vec<Hook>,
+    #[serde(rename = "preStop")]
+    #[serde(default)]
+    pub pre_stop: Vec<Hook>,
+    #[serde(rename = "postStop")]
+    #[serde(default)]
+    pub post_stop: Vec<Hook>,
+}
+
+fn default_stop_signal() -> String {
+    "term".to_string()
+}
+
+fn default_stop_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_cwd() -> String {
+    ".".to_string()
+}
+
+fn default_dependency_timeout_ms() -> u64 {
+    60_000
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,16 +234,43 @@ pub struct ServiceDefinition {
     pub id: String,
     pub name: String,
     pub binary: String,
+    #[serde(default)]
     pub args: Vec<String>,
+    #[serde(default)]
     pub env: HashMap<String, String>,
+    #[serde(default = "default_cwd")]
     pub cwd: String,
+    #[serde(default)]
+    // Referred to as ${port.<name>}; see template.rs for all placeholders.
     pub ports: Vec<PortDef>,
     #[serde(rename = "dependsOn")]
+    #[serde(default)]
     pub depends_on: Vec<String>,
+    #[serde(rename = "dependencyTimeoutMs")]
+    #[serde(default = "default_dependency_timeout_ms")]
+    pub dependency_timeout_ms: u64,
     #[serde(rename = "healthCheck")]
+    #[serde(default)]
     pub health_check: HealthCheck,
     #[serde(rename = "restartPolicy")]
+    #[serde(default)]
     pub restart_policy: RestartPolicy,
+    #[serde(rename = "stopPolicy")]
+    #[serde(default)]
+    pub stop_policy: StopPolicy,
+    #[serde(rename = "reloadPolicy")]
+    #[serde(default)]
+    pub reload_policy: Option<ReloadPolicy>,
+    #[serde(default)]
+    pub hooks: LifecycleHooks,
+    // Parser for captured output: "postgres", "mariadb", "php-server",
+    // "mailpit", "json" or none for plain lines.
+    #[serde(rename = "logFormat")]
+    #[serde(default)]
+    pub log_format: Option<String>,
+    // Value of ${version}; replaced by ServiceConfig.version when one is pinned.
+    #[serde(default)]
+    pub version: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,13 +282,145 @@ pub struct ServiceState {
     pub last_error: Option<String>,
     #[serde(rename = "lastUpdated")]
     pub last_updated: String,
+    #[serde(rename = "stopMethod")]
+    #[serde(default)]
+    pub stop_method: Option<String>,
+    #[serde(rename = "blockedBy")]
+    #[serde(default)]
+    pub blocked_by: Option<String>,
+    // Set when the process crashed; kept through restarting and crash-loop.
+    #[serde(rename = "lastExitCode")]
+    #[serde(default)]
+    pub last_exit_code: Option<i32>,
+    #[serde(rename = "lastSignal")]
+    #[serde(default)]
+    pub last_signal: Option<i32>,
+    #[serde(rename = "stderrTail")]
+    #[serde(default)]
+    pub stderr_tail: Vec<String>,
+}
+
+impl ServiceState {
+    pub fn new(id: &str, state: &str, last_updated: String) -> Self {
+        Self {
+            id: id.to_string(),
+            state: state.to_string(),
+            pid: None,
+            last_error: None,
+            last_updated,
+            stop_method: None,
+            blocked_by: None,
+            last_exit_code: None,
+            last_signal: None,
+            stderr_tail: Vec::new(),
+        }
+    }
+}
+
+// Result of applying a ServiceConfig to a service without a forced restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReloadOutcome {
+    pub service: String,
+    // "reloaded", "restarted", "restart-required" or "not-running".
+    pub outcome: String,
+    // "command" or "signal" when reloaded.
+    pub method: Option<String>,
+    // Why the change cannot be reloaded, e.g. "port main changed: 5432 -> 5401".
+    pub reasons: Vec<String>,
+    #[serde(rename = "renderedFiles")]
+    pub rendered_files: Vec<String>,
+    pub state: ServiceState,
+}
+
+// What starting a service would run, resolved without spawning anything.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchPlan {
+    pub service: String,
+    // Absolute path, or where the binary was expected when it is missing.
+    pub binary: String,
+    #[serde(rename = "binaryFound")]
+    pub binary_found: bool,
+    pub args: Vec<String>,
+    pub cwd: String,
+    // Set on top of the app's environment; includes the scoped PATH.
+    pub env: HashMap<String, String>,
+    pub ports: Vec<PortDef>,
+    #[serde(rename = "healthCheck")]
+    pub health_check: HealthCheck,
+    #[serde(rename = "dependsOn")]
+    pub depends_on: Vec<String>,
+    // Config files rendered from *.tmpl right before the spawn.
+    #[serde(rename = "generatedFiles")]
+    pub generated_files: Vec<String>,
+    // Problems the start would run into, e.g. a missing binary or busy port.
+    pub warnings: Vec<String>,
+}
+
+// One process lifetime of a service, from spawn to exit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub service: String,
+    // Per-service, increasing; identifies the run for services.run.
+    pub run: u64,
+    pub pid: Option<u32>,
+    #[serde(rename = "startedAt")]
+    pub started_at: String,
+    #[serde(rename = "stoppedAt")]
+    #[serde(default)]
+    pub stopped_at: Option<String>,
+    #[serde(rename = "uptimeSecs")]
+    #[serde(default)]
+    pub uptime_secs: Option<u64>,
+    // "running", "stopped", "exited", "crashed", "failed" or "lost".
+    pub outcome: String,
+    #[serde(rename = "exitCode")]
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub signal: Option<i32>,
+    // Why this run was started when it was not a plain start.
+    #[serde(rename = "restartReason")]
+    #[serde(default)]
+    pub restart_reason: Option<String>,
+    #[serde(rename = "stopMethod")]
+    #[serde(default)]
+    pub stop_method: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+    // Last stderr lines, captured when the run ended on its own.
+    #[serde(rename = "stderrTail")]
+    #[serde(default)]
+    pub stderr_tail: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeakedProcess {
+    pub service: String,
+    pub pid: u32,
+    pub name: String,
+    #[serde(rename = "startedAt")]
+    pub started_at: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
+    // RFC 3339 UTC with milliseconds, e.g. "2025-01-01T00:00:12.345Z".
     pub ts: String,
+    // Per-service, strictly increasing; orders lines that share a timestamp.
+    #[serde(default)]
+    pub seq: u64,
     pub level: String,
     pub service: String,
     pub message: String,
     pub fields: HashMap<String, String>,
 }
+
+// The tail handed out when a window subscribes to a service's log lines.
+// Lines may be in the tail and still arrive as events afterwards; events
+// with `seq <= last_seq` are duplicates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogSnapshot {
+    pub entries: Vec<LogEntry>,
+    #[serde(rename = "lastSeq")]
+    pub last_seq: u64,
+}
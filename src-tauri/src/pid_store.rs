@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PidRecord {
+    pub pid: u32,
+    // Process start time (seconds since epoch) as reported by the OS; guards
+    // against adopting an unrelated process that reused the pid.
+    #[serde(rename = "startedAt")]
+    pub started_at: u64,
+    pub binary: String,
+    pub ports: Vec<u16>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PidStore {
+    dir: PathBuf,
+}
+
+impl PidStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn list(&self) -> HashMap<String, PidRecord> {
+        let mut records = HashMap::new();
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(_) => return records,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let id = match path.file_stem() {
+                Some(stem) => stem.to_string_lossy().to_string(),
+                None => continue,
+            };
+            let record = fs::read_to_string(&path)
+                .ok()
+                .and_then(|raw| serde_json::from_str::<PidRecord>(&raw).ok());
+            match record {
+                Some(record) => {
+                    records.insert(id, record);
+                }
+                // Unreadable pidfiles cannot be acted on; drop them.
+                None => {
+                    let _ = fs::remove_file(&path);
+                }
+            }
+        }
+        records
+    }
+
+    pub fn save(&self, id: &str, record: &PidRecord) -> Result<(), String> {
+        fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let raw = serde_json::to_string_pretty(record).map_err(|e| e.to_string())?;
+        let tmp = self.dir.join(format!("{id}.json.tmp"));
+        fs::write(&tmp, raw).map_err(|e| e.to_string())?;
+        fs::rename(&tmp, self.path(id)).map_err(|e| e.to_string())
+    }
+
+    pub fn remove(&self, id: &str) -> Result<(), String> {
+        let path = self.path(id);
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+}
@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus};
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessStatus, Signal, System};
+
+pub fn parse_signal(name: &str) -> Result<Signal, String> {
+    let normalized = name.trim().to_ascii_lowercase();
+    let normalized = normalized.strip_prefix("sig").unwrap_or(&normalized);
+    match normalized {
+        "term" => Ok(Signal::Term),
+        "int" => Ok(Signal::Interrupt),
+        "quit" => Ok(Signal::Quit),
+        "hup" => Ok(Signal::Hangup),
+        "usr1" => Ok(Signal::User1),
+        "usr2" => Ok(Signal::User2),
+        "kill" => Ok(Signal::Kill),
+        _ => Err(format!("unsupported signal: {name}")),
+    }
+}
+
+pub fn send_signal(pid: u32, signal: &str) -> Result<(), String> {
+    let signal = parse_signal(signal)?;
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    if !system.refresh_process(pid) {
+        return Err(format!("process {pid} not found"));
+    }
+    let process = system
+        .process(pid)
+        .ok_or_else(|| format!("process {pid} not found"))?;
+    match process.kill_with(signal) {
+        Some(true) => Ok(()),
+        Some(false) => Err(format!("failed to signal process {pid}")),
+        None => Err(format!("signal {signal:?} not supported on this platform")),
+    }
+}
+
+// Kills whatever is left of the process group led by `pid`, or of its job
+// object on Windows. Does nothing once the whole group is gone.
+pub fn kill_group(pid: u32) {
+    #[cfg(unix)]
+    unsafe {
+        libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+    }
+    #[cfg(windows)]
+    job::terminate(pid);
+}
+
+// Ties the child's lifetime to a job object on Windows, so everything it
+// spawns can be killed with it even after being detached from the tree.
+// Unix uses the process group set up by `isolate` instead.
+pub fn contain(child: &Child) -> Result<(), String> {
+    #[cfg(windows)]
+    {
+        job::assign(child)
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = child;
+        Ok(())
+    }
+}
+
+// Drops the job object of a process that has exited. Closing the handle
+// kills anything still in the job.
+pub fn release(pid: u32) {
+    #[cfg(windows)]
+    job::release(pid);
+    #[cfg(not(windows))]
+    let _ = pid;
+}
+
+#[cfg(windows)]
+mod job {
+    use once_cell::sync::Lazy;
+    use std::collections::HashMap;
+    use std::os::windows::io::AsRawHandle;
+    use std::process::Child;
+    use std::sync::Mutex;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+        SetInformationJobObject, TerminateJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+
+    // Job handles by the pid of the process they were created for.
+    static JOBS: Lazy<Mutex<HashMap<u32, HANDLE>>> = Lazy::new(Default::default);
+
+    pub fn assign(child: &Child) -> Result<(), String> {
+        unsafe {
+            let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+            if job == 0 {
+                return Err(std::io::Error::last_os_error().to_string());
+            }
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+            info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            let assigned = SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                &info as *const JOBOBJECT_EXTENDED_LIMIT_INFORMATION as *const _,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            ) != 0
+                && AssignProcessToJobObject(job, child.as_raw_handle() as HANDLE) != 0;
+            if !assigned {
+                let err = std::io::Error::last_os_error().to_string();
+                CloseHandle(job);
+                return Err(err);
+            }
+            if let Some(old) = JOBS.lock().expect("jobs lock").insert(child.id(), job) {
+                CloseHandle(old);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn terminate(pid: u32) -> bool {
+        match JOBS.lock().expect("jobs lock").remove(&pid) {
+            Some(job) => unsafe {
+                let killed = TerminateJobObject(job, 1) != 0;
+                CloseHandle(job);
+                killed
+            },
+            None => false,
+        }
+    }
+
+    pub fn release(pid: u32) {
+        if let Some(job) = JOBS.lock().expect("jobs lock").remove(&pid) {
+            unsafe {
+                CloseHandle(job);
+            }
+        }
+    }
+}
+
+pub fn wait_for_exit(child: &mut Child, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => return true,
+            Ok(None) => {}
+            Err(_) => return false,
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+// Signal that terminated a child; only unix reports one.
+pub fn exit_signal(status: &ExitStatus) -> Option<i32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        status.signal()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = status;
+        None
+    }
+}
+
+pub fn start_time(pid: u32) -> Option<u64> {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    if !system.refresh_process(pid) {
+        return None;
+    }
+    system.process(pid).map(|process| process.start_time())
+}
+
+pub fn is_alive(pid: u32) -> bool {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    if !system.refresh_process(pid) {
+        return false;
+    }
+    matches!(system.process(pid), Some(process) if process.status() != ProcessStatus::Zombie)
+}
+
+// True when `pid` is still the process we spawned: same start time and, when
+// the OS lets us read it, the same executable name.
+pub fn matches(pid: u32, started_at: u64, binary: &str) -> bool {
+    let sys_pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    if !system.refresh_process(sys_pid) {
+        return false;
+    }
+    let process = match system.process(sys_pid) {
+        Some(process) => process,
+        None => return false,
+    };
+    if process.status() == ProcessStatus::Zombie || process.start_time() != started_at {
+        return false;
+    }
+    let expected = Path::new(binary).file_name();
+    match (expected, process.exe().and_then(|exe| exe.file_name())) {
+        (Some(expected), Some(actual)) => expected == actual,
+        _ => true,
+    }
+}
+
+pub fn wait_for_pid_exit(pid: u32, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if !is_alive(pid) {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+pub fn kill_pid(pid: u32) -> Result<(), String> {
+    send_signal(pid, "kill")
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessRef {
+    pub pid: u32,
+    pub started_at: u64,
+    pub name: String,
+}
+
+// Puts the spawned process in its own process group so the whole tree can be
+// told apart from the app and killed together with `kill_group`.
+pub fn isolate(cmd: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
+    }
+}
+
+pub fn descendants(pid: u32) -> Vec<ProcessRef> {
+    let mut system = System::new();
+    system.refresh_processes();
+    let mut children: HashMap<Pid, Vec<Pid>> = HashMap::new();
+    for (child, process) in system.processes() {
+        // Linux lists threads as processes too; they die with their owner.
+        if process.thread_kind().is_some() {
+            continue;
+        }
+        if let Some(parent) = process.parent() {
+            children.entry(parent).or_default().push(*child);
+        }
+    }
+    let mut found = Vec::new();
+    let mut queue = vec![Pid::from_u32(pid)];
+    while let Some(current) = queue.pop() {
+        for child in children.get(&current).into_iter().flatten() {
+            if let Some(process) = system.process(*child) {
+                found.push(ProcessRef {
+                    pid: child.as_u32(),
+                    started_at: process.start_time(),
+                    name: process.name().to_string(),
+                });
+            }
+            queue.push(*child);
+        }
+    }
+    found
+}
+
+// Same pid and same start time, so a reused pid is never mistaken for ours.
+pub fn is_same_process(process: &ProcessRef) -> bool {
+    is_alive(process.pid) && start_time(process.pid) == Some(process.started_at)
+}
+
+// Kills the given processes and returns the ones that are still alive.
+pub fn kill_processes(processes: &[ProcessRef]) -> Vec<ProcessRef> {
+    for process in processes {
+        if is_same_process(process) {
+            let _ = kill_pid(process.pid);
+        }
+    }
+    processes
+        .iter()
+        .filter(|process| {
+            !wait_for_pid_exit(process.pid, Duration::from_millis(500)) && is_same_process(process)
+        })
+        .cloned()
+        .collect()
+}
+
+pub fn kill_tree(pid: u32) -> Result<(), String> {
+    #[cfg(windows)]
+    {
+        if job::terminate(pid) {
+            return Ok(());
+        }
+        let status = Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .map_err(|e| e.to_string())?;
+        if status.success() {
+            return Ok(());
+        }
+        return kill_pid(pid);
+    }
+    #[cfg(not(windows))]
+    {
+        // Snapshot first: once the root dies its children are reparented.
+        let tree = descendants(pid);
+        let result = kill_pid(pid);
+        kill_group(pid);
+        kill_processes(&tree);
+        result
+    }
+}
@@ -0,0 +1,121 @@
+use crate::models::RunRecord;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+// Runs kept per service; older ones are dropped when a new run starts.
+const MAX_RUNS: usize = 50;
+
+// Past and current runs, one JSON file per service. The last record is the
+// open one while its process is alive.
+#[derive(Debug, Clone)]
+pub struct RunHistory {
+    dir: PathBuf,
+    // Runs are opened by launch workers and closed by the tick thread.
+    lock: Arc<Mutex<()>>,
+}
+
+impl RunHistory {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    // Oldest first.
+    pub fn list(&self, id: &str) -> Vec<RunRecord> {
+        let _guard = self.lock.lock().expect("run history lock");
+        self.load(id)
+    }
+
+    pub fn get(&self, id: &str, run: u64) -> Option<RunRecord> {
+        self.list(id).into_iter().find(|record| record.run == run)
+    }
+
+    // Services that have any recorded runs.
+    pub fn services(&self) -> Vec<String> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+        let mut ids: Vec<String> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    // Appends a run with the next number; a run that is still open is closed
+    // as lost first, since its process can no longer be tracked.
+    pub fn open(&self, mut record: RunRecord) -> Result<RunRecord, String> {
+        let _guard = self.lock.lock().expect("run history lock");
+        let mut runs = self.load(&record.service);
+        if let Some(last) = runs.last_mut() {
+            if last.stopped_at.is_none() {
+                finish(last, "lost", &record.started_at);
+            }
+        }
+        record.run = runs.last().map(|last| last.run + 1).unwrap_or(1);
+        runs.push(record.clone());
+        if runs.len() > MAX_RUNS {
+            runs.drain(..runs.len() - MAX_RUNS);
+        }
+        self.save(&record.service, &runs)?;
+        Ok(record)
+    }
+
+    // Ends the open run of a service, if there is one, and lets `update` fill
+    // in how it ended.
+    pub fn close(
+        &self,
+        id: &str,
+        outcome: &str,
+        stopped_at: &str,
+        update: impl FnOnce(&mut RunRecord),
+    ) -> Result<Option<RunRecord>, String> {
+        let _guard = self.lock.lock().expect("run history lock");
+        let mut runs = self.load(id);
+        let last = match runs.last_mut() {
+            Some(last) if last.stopped_at.is_none() => last,
+            _ => return Ok(None),
+        };
+        finish(last, outcome, stopped_at);
+        update(last);
+        let closed = last.clone();
+        self.save(id, &runs)?;
+        Ok(Some(closed))
+    }
+
+    fn load(&self, id: &str) -> Vec<RunRecord> {
+        fs::read_to_string(self.path(id))
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, id: &str, runs: &[RunRecord]) -> Result<(), String> {
+        fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let raw = serde_json::to_string_pretty(runs).map_err(|e| e.to_string())?;
+        let tmp = self.dir.join(format!("{id}.json.tmp"));
+        fs::write(&tmp, raw).map_err(|e| e.to_string())?;
+        fs::rename(&tmp, self.path(id)).map_err(|e| e.to_string())
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+}
+
+fn finish(record: &mut RunRecord, outcome: &str, stopped_at: &str) {
+    record.outcome = outcome.to_string();
+    record.stopped_at = Some(stopped_at.to_string());
+    // Timestamps are seconds since the epoch, see service_manager::now_ts.
+    record.uptime_secs = match (record.started_at.parse::<u64>(), stopped_at.parse::<u64>()) {
+        (Ok(started), Ok(stopped)) => Some(stopped.saturating_sub(started)),
+        _ => None,
+    };
+}
@@ -4,6 +4,7 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::drivers;
 use crate::models::PortDef;
 use crate::installer;
 use flate2::read::GzDecoder;
@@ -60,16 +61,18 @@ pub struct RuntimeSources {
     pub manifest_checksum: Option<String>,
 }
 
+// Where a driver's binaries come from when the runtime bundle lacks them.
 #[derive(Debug, Clone)]
-struct OfficialSource {
-    url: String,
-    archive: ArchiveKind,
-    binary_names: Vec<String>,
-    target_binary: String,
+pub struct OfficialSource {
+    pub url: String,
+    pub archive: ArchiveKind,
+    // Names to look for inside the archive, in order of preference.
+    pub binary_names: Vec<String>,
+    pub target_binary: String,
 }
 
 #[derive(Debug, Clone, Copy)]
-enum ArchiveKind {
+pub enum ArchiveKind {
     Zip,
     TarGz,
     TarXz,
@@ -185,10 +188,15 @@ impl RuntimeManager {
             self.root.join(path)
         };
         if resolved.exists() {
-            Ok(resolved)
-        } else {
-            Err(format!("binary not found: {}", resolved.display()))
+            return Ok(resolved);
+        }
+        // Bare names ("redis-server") may refer to a tool installed on the host.
+        if path.components().count() == 1 {
+            if let Some(found) = find_in_path(binary) {
+                return Ok(found);
+            }
         }
+        Err(format!("binary not found: {}", resolved.display()))
     }
 
     pub fn get_manifest(&self) -> Result<RuntimeManifest, String> {
@@ -332,13 +340,10 @@ pub fn default_manifest() -> RuntimeManifest {
 }
 
 pub fn default_versions() -> HashMap<String, String> {
-    let mut versions = HashMap::new();
-    versions.insert("php".to_string(), "8.3.2".to_string());
-    versions.insert("node".to_string(), "20.11.1".to_string());
-    versions.insert("postgres".to_string(), "16.2".to_string());
-    versions.insert("mariadb".to_string(), "10.11.6".to_string());
-    versions.insert("mailpit".to_string(), "1.15.0".to_string());
-    versions
+    drivers::all()
+        .iter()
+        .map(|driver| (driver.id().to_string(), driver.default_version().to_string()))
+        .collect()
 }
 
 pub fn os_arch_tag() -> (String, String) {
@@ -364,102 +369,22 @@ pub fn bin_path_for(service: &str, version: &str) -> String {
 
 pub fn default_service_binaries() -> Vec<ServiceBinary> {
     let (os, arch) = os_arch_tag();
-    let versions = default_versions();
-    let mut services = Vec::new();
-    let php_version = versions.get("php").cloned().unwrap_or_default();
-    services.push(ServiceBinary {
-        name: "php".to_string(),
-        version: php_version.clone(),
-        os: os.clone(),
-        arch: arch.clone(),
-        checksum: "".to_string(),
-        size: 0,
-        bin_path: bin_path_for("php", &php_version),
-        default_ports: vec![PortDef {
-            name: "fpm".to_string(),
-            port: 9000,
-            protocol: "tcp".to_string(),
-        }],
-        env: {
-            let mut env = HashMap::new();
-            env.insert("PHP_INI_SCAN_DIR".to_string(), "runtime/config/php".to_string());
-            env
-        },
-        args: Vec::new(),
-    });
-    let node_version = versions.get("node").cloned().unwrap_or_default();
-    services.push(ServiceBinary {
-        name: "node".to_string(),
-        version: node_version.clone(),
-        os: os.clone(),
-        arch: arch.clone(),
-        checksum: "".to_string(),
-        size: 0,
-        bin_path: bin_path_for("node", &node_version),
-        default_ports: vec![PortDef {
-            name: "http".to_string(),
-            port: 3000,
-            protocol: "tcp".to_string(),
-        }],
-        env: HashMap::new(),
-        args: Vec::new(),
-    });
-    let postgres_version = versions.get("postgres").cloned().unwrap_or_default();
-    services.push(ServiceBinary {
-        name: "postgres".to_string(),
-        version: postgres_version.clone(),
-        os: os.clone(),
-        arch: arch.clone(),
-        checksum: "".to_string(),
-        size: 0,
-        bin_path: bin_path_for("postgres", &postgres_version),
-        default_ports: vec![PortDef {
-            name: "db".to_string(),
-            port: 5432,
-            protocol: "tcp".to_string(),
-        }],
-        env: {
-            let mut env = HashMap::new();
-            env.insert("PGDATA".to_string(), "runtime/data/postgres".to_string());
-            env
-        },
-        args: vec!["-D".to_string(), "runtime/data/postgres".to_string()],
-    });
-    let mariadb_version = versions.get("mariadb").cloned().unwrap_or_default();
-    services.push(ServiceBinary {
-        name: "mariadb".to_string(),
-        version: mariadb_version.clone(),
-        os: os.clone(),
-        arch: arch.clone(),
-        checksum: "".to_string(),
-        size: 0,
-        bin_path: bin_path_for("mariadb", &mariadb_version),
-        default_ports: vec![PortDef {
-            name: "db".to_string(),
-            port: 3306,
-            protocol: "tcp".to_string(),
-        }],
-        env: HashMap::new(),
-        args: Vec::new(),
-    });
-    let mailpit_version = versions.get("mailpit").cloned().unwrap_or_default();
-    services.push(ServiceBinary {
-        name: "mailpit".to_string(),
-        version: mailpit_version.clone(),
-        os,
-        arch,
-        checksum: "".to_string(),
-        size: 0,
-        bin_path: bin_path_for("mailpit", &mailpit_version),
-        default_ports: vec![PortDef {
-            name: "http".to_string(),
-            port: 8025,
-            protocol: "tcp".to_string(),
-        }],
-        env: HashMap::new(),
-        args: Vec::new(),
-    });
-    services
+    drivers::all()
+        .iter()
+        .map(|driver| drivers::service_binary(driver.as_ref(), &os, &arch))
+        .collect()
+}
+
+fn find_in_path(binary: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path).find_map(|dir| {
+        let candidate = dir.join(binary);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        let exe = dir.join(format!("{binary}.exe"));
+        (cfg!(target_os = "windows") && exe.is_file()).then_some(exe)
+    })
 }
 
 fn now_ts() -> String {
@@ -533,7 +458,8 @@ fn install_official_runtime(
     os: &str,
     arch: &str,
 ) -> Result<(), String> {
-    let source = official_source_for(name, version, os, arch)
+    let source = drivers::find(name)
+        .and_then(|driver| driver.official_source(version, os, arch))
         .ok_or_else(|| "official runtime source not available".to_string())?;
     let archive_ext = archive_extension(source.archive);
     let cache_root = manager.root.join("runtime/cache/official");
@@ -584,7 +510,7 @@ fn install_official_runtime(
     Ok(())
 }
 
-fn archive_extension(kind: ArchiveKind) -> &'static str {
+pub fn archive_extension(kind: ArchiveKind) -> &'static str {
     match kind {
         ArchiveKind::Zip => "zip",
         ArchiveKind::TarGz => "tar.gz",
@@ -592,141 +518,6 @@ fn archive_extension(kind: ArchiveKind) -> &'static str {
     }
 }
 
-fn official_source_for(
-    name: &str,
-    version: &str,
-    os: &str,
-    arch: &str,
-) -> Option<OfficialSource> {
-    let target_binary = if os == "windows" {
-        format!("{name}.exe")
-    } else {
-        name.to_string()
-    };
-    match name {
-        "node" => {
-            let platform = match os {
-                "windows" => "win",
-                "macos" => "darwin",
-                "linux" => "linux",
-                _ => return None,
-            };
-            let arch = match arch {
-                "x64" => "x64",
-                "arm64" => "arm64",
-                _ => return None,
-            };
-            let archive = if os == "windows" {
-                ArchiveKind::Zip
-            } else {
-                ArchiveKind::TarXz
-            };
-            let ext = archive_extension(archive);
-            Some(OfficialSource {
-                url: format!(
-                    "https://nodejs.org/dist/v{version}/node-v{version}-{platform}-{arch}.{ext}"
-                ),
-                archive,
-                binary_names: vec![target_binary.clone()],
-                target_binary,
-            })
-        }
-        "mailpit" => {
-            let platform = match os {
-                "windows" => "windows",
-                "macos" => "darwin",
-                "linux" => "linux",
-                _ => return None,
-            };
-            let arch = match arch {
-                "x64" => "amd64",
-                "arm64" => "arm64",
-                _ => return None,
-            };
-            let archive = if os == "windows" {
-                ArchiveKind::Zip
-            } else {
-                ArchiveKind::TarGz
-            };
-            let ext = archive_extension(archive);
-            Some(OfficialSource {
-                url: format!(
-                    "https://github.com/axllent/mailpit/releases/download/v{version}/mailpit-{platform}-{arch}.{ext}"
-                ),
-                archive,
-                binary_names: vec![target_binary.clone()],
-                target_binary,
-            })
-        }
-        "php" => {
-            if os != "windows" || arch != "x64" {
-                return None;
-            }
-            Some(OfficialSource {
-                url: format!(
-                    "https://windows.php.net/downloads/releases/php-{version}-Win32-vs16-x64.zip"
-                ),
-                archive: ArchiveKind::Zip,
-                binary_names: vec![target_binary.clone()],
-                target_binary,
-            })
-        }
-        "postgres" => {
-            let (platform, archive) = match os {
-                "windows" => ("windows-x64", ArchiveKind::Zip),
-                "linux" => ("linux-x64", ArchiveKind::TarGz),
-                "macos" => ("osx", ArchiveKind::Zip),
-                _ => return None,
-            };
-            if arch != "x64" {
-                return None;
-            }
-            let ext = archive_extension(archive);
-            Some(OfficialSource {
-                url: format!(
-                    "https://get.enterprisedb.com/postgresql/postgresql-{version}-{platform}-binaries.{ext}"
-                ),
-                archive,
-                binary_names: vec![target_binary.clone()],
-                target_binary,
-            })
-        }
-        "mariadb" => {
-            let (platform, archive) = match os {
-                "windows" => ("winx64", ArchiveKind::Zip),
-                "linux" => ("linux-x86_64", ArchiveKind::TarGz),
-                "macos" => ("macosx", ArchiveKind::TarGz),
-                _ => return None,
-            };
-            if arch != "x64" {
-                return None;
-            }
-            let ext = archive_extension(archive);
-            Some(OfficialSource {
-                url: format!(
-                    "https://archive.mariadb.org/mariadb-{version}/{platform}/mariadb-{version}-{platform}.{ext}"
-                ),
-                archive,
-                binary_names: vec![
-                    target_binary.clone(),
-                    if os == "windows" {
-                        "mariadbd.exe".to_string()
-                    } else {
-                        "mariadbd".to_string()
-                    },
-                    if os == "windows" {
-                        "mysqld.exe".to_string()
-                    } else {
-                        "mysqld".to_string()
-                    },
-                ],
-                target_binary,
-            })
-        }
-        _ => None,
-    }
-}
-
 fn extract_archive(archive: &PathBuf, dest: &PathBuf, kind: ArchiveKind) -> Result<(), String> {
     match kind {
         ArchiveKind::Zip => extract_zip_to(archive, dest),
@@ -150,9 +150,252 @@ pub fn validate_service_config(value: &Value) -> Result<(), String> {
         .get("args")
         .and_then(|v| v.as_array())
         .ok_or_else(|| "args is required".to_string())?;
+    if let Some(rotation) = value.get("logRotation") {
+        let rotation = rotation
+            .as_object()
+            .ok_or_else(|| "logRotation must be an object".to_string())?;
+        if let Some(size) = rotation.get("maxSizeBytes") {
+            if !matches!(size.as_u64(), Some(size) if size > 0) {
+                return Err("logRotation.maxSizeBytes must be a positive number".to_string());
+            }
+        }
+        for key in ["maxFiles", "maxAgeDays"] {
+            if let Some(count) = rotation.get(key) {
+                if !matches!(count.as_u64(), Some(count) if count <= u32::MAX as u64) {
+                    return Err(format!("logRotation.{key} must be a non-negative number"));
+                }
+            }
+        }
+        if let Some(compress) = rotation.get("compress") {
+            if !compress.is_boolean() {
+                return Err("logRotation.compress must be a boolean".to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_profile(value: &Value) -> Result<(), String> {
+    let schema_version = value
+        .get("schemaVersion")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| "schemaVersion is required".to_string())?;
+    if schema_version != 1 {
+        return Err("unsupported schemaVersion".to_string());
+    }
+    let name = value
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "name is required".to_string())?;
+    // Profile names are file names too.
+    if !is_valid_service_id(name) {
+        return Err(format!("invalid profile name: {name}"));
+    }
+    let services = value
+        .get("services")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| "services is required".to_string())?;
+    if services.is_empty() {
+        return Err("profile must list at least one service".to_string());
+    }
+    let mut seen = Vec::new();
+    for service in services {
+        let id = service
+            .get("id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "services[].id is required".to_string())?;
+        if seen.contains(&id) {
+            return Err(format!("duplicate service in profile: {id}"));
+        }
+        seen.push(id);
+        if let Some(ports) = service.get("ports") {
+            let ports = ports
+                .as_object()
+                .ok_or_else(|| format!("ports must be an object for {id}"))?;
+            for (key, port) in ports {
+                if !matches!(port.as_u64(), Some(port) if port > 0 && port <= u16::MAX as u64) {
+                    return Err(format!("invalid port {key} for {id}"));
+                }
+            }
+        }
+        if let Some(env) = service.get("env") {
+            let env = env
+                .as_object()
+                .ok_or_else(|| format!("env must be an object for {id}"))?;
+            for (key, value) in env {
+                if !is_valid_env_key(key) {
+                    return Err(format!("invalid env key: {key}"));
+                }
+                if !value.is_string() {
+                    return Err(format!("invalid env value for {key}"));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Custom service definitions from app/config/services.d/*.json.
+pub fn validate_service_definition(value: &Value) -> Result<(), String> {
+    let schema_version = value
+        .get("schemaVersion")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| "schemaVersion is required".to_string())?;
+    if schema_version != 1 {
+        return Err("unsupported schemaVersion".to_string());
+    }
+    let id = value
+        .get("id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "id is required".to_string())?;
+    if !is_valid_service_id(id) {
+        return Err(format!("invalid service id: {id}"));
+    }
+    for field in ["name", "binary"] {
+        let val = value
+            .get(field)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| format!("{field} is required"))?;
+        if val.trim().is_empty() {
+            return Err(format!("{field} is required"));
+        }
+    }
+    for field in ["args", "dependsOn"] {
+        if let Some(items) = value.get(field) {
+            let items = items
+                .as_array()
+                .ok_or_else(|| format!("{field} must be an array"))?;
+            if items.iter().any(|item| !item.is_string()) {
+                return Err(format!("{field} must contain strings"));
+            }
+        }
+    }
+    if let Some(cwd) = value.get("cwd") {
+        cwd.as_str()
+            .ok_or_else(|| "cwd must be a string".to_string())?;
+    }
+    if let Some(env) = value.get("env") {
+        let env = env
+            .as_object()
+            .ok_or_else(|| "env must be an object".to_string())?;
+        for (key, value) in env {
+            if !is_valid_env_key(key) {
+                return Err(format!("invalid env key: {key}"));
+            }
+            value
+                .as_str()
+                .ok_or_else(|| format!("invalid env value for {key}"))?;
+        }
+    }
+    if let Some(ports) = value.get("ports") {
+        let ports = ports
+            .as_array()
+            .ok_or_else(|| "ports must be an array".to_string())?;
+        for port in ports {
+            let name = port
+                .get("name")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| "port name is required".to_string())?;
+            let number = port
+                .get("port")
+                .and_then(|v| v.as_u64())
+                .ok_or_else(|| format!("invalid port for {name}"))?;
+            if number == 0 || number > u16::MAX as u64 {
+                return Err(format!("port out of range for {name}"));
+            }
+            let protocol = port
+                .get("protocol")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| format!("protocol is required for {name}"))?;
+            if protocol != "tcp" && protocol != "udp" {
+                return Err(format!("protocol must be tcp or udp for {name}"));
+            }
+        }
+    }
+    if let Some(check) = value.get("healthCheck") {
+        let kind = check
+            .get("type")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "healthCheck.type is required".to_string())?;
+        if !matches!(kind, "pid" | "port" | "http" | "exec") {
+            return Err(format!("unsupported healthCheck.type: {kind}"));
+        }
+        let target = check.get("target").and_then(|v| v.as_str()).unwrap_or("");
+        if matches!(kind, "port" | "http") && target.trim().is_empty() {
+            return Err("healthCheck.target is required".to_string());
+        }
+        if kind == "exec" {
+            let command = check
+                .get("command")
+                .and_then(|v| v.as_array())
+                .ok_or_else(|| "healthCheck.command is required".to_string())?;
+            if command.is_empty() || !command.iter().all(|arg| arg.is_string()) {
+                return Err("healthCheck.command must be a non-empty list of strings".to_string());
+            }
+        }
+        for field in ["timeoutMs", "intervalMs"] {
+            check
+                .get(field)
+                .and_then(|v| v.as_u64())
+                .ok_or_else(|| format!("healthCheck.{field} is required"))?;
+        }
+    }
+    if let Some(policy) = value.get("restartPolicy") {
+        for field in ["maxRetries", "backoffMs"] {
+            policy
+                .get(field)
+                .and_then(|v| v.as_u64())
+                .ok_or_else(|| format!("restartPolicy.{field} is required"))?;
+        }
+    }
+    if let Some(hooks) = value.get("hooks") {
+        for phase in ["preStart", "postStart", "preStop", "postStop"] {
+            let Some(list) = hooks.get(phase) else {
+                continue;
+            };
+            let list = list
+                .as_array()
+                .ok_or_else(|| format!("hooks.{phase} must be an array"))?;
+            for hook in list {
+                let command = hook
+                    .get("command")
+                    .and_then(|v| v.as_array())
+                    .ok_or_else(|| format!("hooks.{phase} command is required"))?;
+                if command.is_empty() || !command.iter().all(|arg| arg.is_string()) {
+                    return Err(format!(
+                        "hooks.{phase} command must be a non-empty list of strings"
+                    ));
+                }
+                if let Some(policy) = hook.get("onFailure") {
+                    let policy = policy.as_str().unwrap_or_default();
+                    if !matches!(policy, "abort" | "warn") {
+                        return Err(format!("unsupported hooks.{phase} onFailure: {policy}"));
+                    }
+                }
+            }
+        }
+    }
+    if let Some(format) = value.get("logFormat") {
+        if !format.is_null() {
+            let format = format
+                .as_str()
+                .ok_or_else(|| "logFormat must be a string".to_string())?;
+            if !matches!(format, "postgres" | "mariadb" | "php-server" | "mailpit" | "json") {
+                return Err(format!("unsupported logFormat: {format}"));
+            }
+        }
+    }
     Ok(())
 }
 
+// Ids become file names (pidfiles, logs, configs).
+fn is_valid_service_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
 fn is_valid_env_key(key: &str) -> bool {
     let mut chars = key.chars();
     match chars.next() {
@@ -1,27 +1,83 @@
-use crate::config_store::ServiceConfig;
-use crate::models::{LogEntry, ServiceDefinition, ServiceState};
+use crate::config_store::{LogRotation, ServiceConfig};
+use crate::drivers::{self, DriverContext};
+use crate::events::{EventBus, ServiceEvent};
+use crate::health;
+use crate::health_history::{HealthHistory, HealthProbe, HealthReport};
+use crate::log_capture::{self, Follower, Tail};
+use crate::log_files::LogFiles;
+use crate::log_parser;
+use crate::log_query::{self, LogPage, LogQuery};
+use crate::models::{
+    Hook, LaunchPlan, LeakedProcess, LogEntry, LogSnapshot, ReloadOutcome, RestartPolicy,
+    RunRecord, ServiceDefinition, ServiceState,
+};
+use crate::pid_store::{PidRecord, PidStore};
+use crate::process;
+use crate::process::ProcessRef;
+use crate::run_history::RunHistory;
 use crate::runtime;
 use crate::runtime::RuntimeManager;
-use std::collections::{HashMap, HashSet};
-use std::io::{BufRead, BufReader};
-use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
-use std::path::PathBuf;
+use crate::secrets::SecretsStore;
+use crate::template::{self, TemplateContext};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{BufRead, BufReader, Read};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
-use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const STDERR_TAIL_LINES: usize = 20;
 
 #[derive(Debug)]
 pub struct ServiceManager {
-    runtime: RuntimeManager,
     definitions: Vec<ServiceDefinition>,
-    states: HashMap<String, ServiceState>,
-    processes: HashMap<String, Child>,
-    restart_attempts: HashMap<String, u32>,
+    shared: Shared,
+}
+
+// Everything a background start or stop worker needs, so it can run without
+// holding the lock around the ServiceManager itself.
+#[derive(Debug, Clone)]
+struct Shared {
+    runtime: RuntimeManager,
+    states: Arc<Mutex<HashMap<String, ServiceState>>>,
+    processes: Arc<Mutex<HashMap<String, Child>>>,
+    // Processes left running by a previous app session, tracked by pid only.
+    adopted: Arc<Mutex<HashMap<String, u32>>>,
+    pids: Option<PidStore>,
+    runs: Option<RunHistory>,
+    // Why the next launch of a service happens; taken when it begins.
+    start_reasons: Arc<Mutex<HashMap<String, String>>>,
+    // Last known descendants of each running service, used to spot orphans.
+    lineage: Arc<Mutex<HashMap<String, Vec<ProcessRef>>>>,
+    leaked: Arc<Mutex<Vec<LeakedProcess>>>,
+    effective: Arc<Mutex<HashMap<String, ServiceDefinition>>>,
+    launches: Arc<Mutex<HashMap<String, u64>>>,
+    next_launch: Arc<AtomicU64>,
+    // Stops still running on a worker; a new launch waits for them.
+    stops: Arc<Mutex<HashMap<String, Arc<StopDone>>>>,
+    // Crash times inside the restart window, oldest first.
+    crashes: Arc<Mutex<HashMap<String, VecDeque<Instant>>>>,
+    // Token of the restart timer that may still fire for a service.
+    pending_restarts: Arc<Mutex<HashMap<String, u64>>>,
+    stderr_tail: Arc<Mutex<HashMap<String, VecDeque<String>>>>,
+    // Threads turning the capture files of running services into log lines.
+    captures: Arc<Mutex<HashMap<String, Follower>>>,
+    healthy: Arc<Mutex<HashMap<String, bool>>>,
+    // Results of the periodic probes made by tick.
+    health_history: HealthHistory,
     logs: Arc<Mutex<HashMap<String, Vec<LogEntry>>>>,
-    log_root: PathBuf,
+    // Next LogEntry.seq per service; survives clearing the buffer.
+    log_seq: Arc<Mutex<HashMap<String, u64>>>,
+    events: EventBus,
+    log_files: LogFiles,
     log_limit: usize,
     health_retries: u32,
+    // Source for ${secret:<key>}; without it such placeholders fail the start.
+    secrets: Option<Arc<Mutex<SecretsStore>>>,
 }
 
 impl ServiceManager {
@@ -32,39 +88,195 @@ impl ServiceManager {
     ) -> Self {
         let _ = std::fs::create_dir_all(&log_root);
         Self {
-            runtime,
             definitions,
-            states: HashMap::new(),
-            processes: HashMap::new(),
-            restart_attempts: HashMap::new(),
-            logs: Arc::new(Mutex::new(HashMap::new())),
-            log_root,
-            log_limit: 2000,
-            health_retries: 5,
+            shared: Shared {
+                runtime,
+                states: Arc::new(Mutex::new(HashMap::new())),
+                processes: Arc::new(Mutex::new(HashMap::new())),
+                adopted: Arc::new(Mutex::new(HashMap::new())),
+                pids: None,
+                runs: None,
+                start_reasons: Arc::new(Mutex::new(HashMap::new())),
+                lineage: Arc::new(Mutex::new(HashMap::new())),
+                leaked: Arc::new(Mutex::new(Vec::new())),
+                effective: Arc::new(Mutex::new(HashMap::new())),
+                launches: Arc::new(Mutex::new(HashMap::new())),
+                next_launch: Arc::new(AtomicU64::new(1)),
+                stops: Arc::new(Mutex::new(HashMap::new())),
+                crashes: Arc::new(Mutex::new(HashMap::new())),
+                pending_restarts: Arc::new(Mutex::new(HashMap::new())),
+                stderr_tail: Arc::new(Mutex::new(HashMap::new())),
+                captures: Arc::new(Mutex::new(HashMap::new())),
+                healthy: Arc::new(Mutex::new(HashMap::new())),
+                health_history: HealthHistory::default(),
+                logs: Arc::new(Mutex::new(HashMap::new())),
+                log_seq: Arc::new(Mutex::new(HashMap::new())),
+                events: EventBus::default(),
+                log_files: LogFiles::new(log_root),
+                log_limit: 2000,
+                health_retries: 5,
+                secrets: None,
+            },
+        }
+    }
+
+    pub fn set_state_dir(&mut self, dir: PathBuf) {
+        self.shared.pids = Some(PidStore::new(dir.join("pids")));
+        self.shared.runs = Some(RunHistory::new(dir.join("runs")));
+    }
+
+    pub fn set_secrets(&mut self, secrets: Arc<Mutex<SecretsStore>>) {
+        self.shared.secrets = Some(secrets);
+    }
+
+    // Adopts services still running from a previous session and cleans up
+    // the ones that are unhealthy or no longer known.
+    pub fn reattach(&mut self) -> Vec<ServiceState> {
+        let store = match &self.shared.pids {
+            Some(store) => store.clone(),
+            None => return Vec::new(),
+        };
+        let mut adopted = Vec::new();
+        for (id, record) in store.list() {
+            if !process::matches(record.pid, record.started_at, &record.binary) {
+                let _ = store.remove(&id);
+                continue;
+            }
+            let def = self
+                .definitions
+                .iter()
+                .find(|d| d.id == id)
+                .map(|def| self.shared.render(def).unwrap_or_else(|_| def.clone()));
+            let health = match &def {
+                Some(def) => self.shared.check_health(def),
+                None => Err("unknown service".to_string()),
+            };
+            match (def, health) {
+                (Some(def), Ok(())) => {
+                    self.shared
+                        .capture_logs(&id, def.log_format.clone(), record.pid, true);
+                    self.shared
+                        .adopted
+                        .lock()
+                        .expect("adopted lock")
+                        .insert(id.clone(), record.pid);
+                    self.shared
+                        .effective
+                        .lock()
+                        .expect("effective lock")
+                        .insert(id.clone(), def);
+                    let state = ServiceState {
+                        pid: Some(record.pid),
+                        ..ServiceState::new(&id, "running", now_ts())
+                    };
+                    self.shared.set_state(state.clone());
+                    self.shared.record_health(&id, &Ok(()));
+                    self.shared
+                        .push_log(&id, "info", &format!("adopted running process {}", record.pid));
+                    adopted.push(state);
+                }
+                (def, health) => {
+                    let reason = health.err().unwrap_or_default();
+                    self.shared.push_log(
+                        &id,
+                        "warn",
+                        &format!("cleaning up orphaned process {}: {reason}", record.pid),
+                    );
+                    let _ = self
+                        .shared
+                        .shutdown(&id, def.as_ref(), Tracked::Adopted(record.pid));
+                    let _ = store.remove(&id);
+                    self.shared.close_run(&id, "stopped", |run| {
+                        run.error = Some(format!("cleaned up after an app restart: {reason}"));
+                    });
+                }
+            }
+        }
+        // Whatever else was running when the app went away ended unobserved.
+        if let Some(runs) = &self.shared.runs {
+            for id in runs.services() {
+                if !adopted.iter().any(|state| state.id == id) {
+                    self.shared.close_run(&id, "lost", |run| {
+                        run.error = Some("exited while the app was not running".to_string());
+                    });
+                }
+            }
         }
+        adopted
+    }
+
+    // Newest first.
+    pub fn runs(&self, id: &str, limit: usize) -> Vec<RunRecord> {
+        let Some(runs) = &self.shared.runs else {
+            return Vec::new();
+        };
+        runs.list(id).into_iter().rev().take(limit).collect()
+    }
+
+    pub fn run(&self, id: &str, run: u64) -> Result<RunRecord, String> {
+        self.shared
+            .runs
+            .as_ref()
+            .and_then(|runs| runs.get(id, run))
+            .ok_or_else(|| format!("run {run} of {id} not found"))
+    }
+
+    // Driver view of a running service, for database dumps and restores.
+    pub fn driver_context(&self, id: &str) -> Result<DriverContext, String> {
+        let def = self
+            .shared
+            .effective
+            .lock()
+            .expect("effective lock")
+            .get(id)
+            .cloned()
+            .ok_or_else(|| format!("{id} is not running"))?;
+        let binary = self.shared.runtime.resolve_binary(&def.binary)?;
+        Ok(self.shared.driver_context(&def, &binary))
+    }
+
+    pub fn definitions(&self) -> &[ServiceDefinition] {
+        &self.definitions
     }
 
     pub fn list(&mut self) -> Vec<ServiceState> {
+        let mut states = self.shared.states.lock().expect("states lock");
         for def in &self.definitions {
-            self.states.entry(def.id.clone()).or_insert_with(|| {
-                ServiceState {
-                    id: def.id.clone(),
-                    state: "stopped".to_string(),
-                    pid: None,
-                    last_error: None,
-                    last_updated: now_ts(),
-                }
-            });
+            states
+                .entry(def.id.clone())
+                .or_insert_with(|| ServiceState::new(&def.id, "stopped", now_ts()));
         }
         self.definitions
             .iter()
-            .filter_map(|def| self.states.get(&def.id).cloned())
+            .filter_map(|def| states.get(&def.id).cloned())
             .collect()
     }
 
+    pub fn state(&self, id: &str) -> Option<ServiceState> {
+        self.shared.state(id)
+    }
+
+    pub fn subscribe(&self) -> Receiver<ServiceEvent> {
+        self.shared.events.subscribe()
+    }
+
+    pub fn wait_for(&self, id: &str, timeout: Duration) -> Option<ServiceState> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let state = self.shared.state(id);
+            let settled =
+                !matches!(&state, Some(s) if s.state == "starting" || s.state == "stopping");
+            if settled || Instant::now() >= deadline {
+                return state;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    // A start requested by the user closes an open crash-loop circuit.
     pub fn start(&mut self, id: &str) -> Result<ServiceState, String> {
-        let mut visiting = HashSet::new();
-        self.start_with_dependencies(id, &mut visiting)
+        self.shared.reset_crashes(id);
+        self.start_planned(id, None)
     }
 
     pub fn start_with_config(
@@ -72,8 +284,8 @@ impl ServiceManager {
         id: &str,
         config: ServiceConfig,
     ) -> Result<ServiceState, String> {
-        let mut visiting = HashSet::new();
-        self.start_with_dependencies_config(id, &mut visiting, Some(config))
+        self.shared.reset_crashes(id);
+        self.start_planned(id, Some(config))
     }
 
     pub fn restart_with_config(
@@ -81,207 +293,566 @@ impl ServiceManager {
         id: &str,
         config: ServiceConfig,
     ) -> Result<ServiceState, String> {
-        let _ = self.stop(id);
-        self.start_with_config(id, config)
+        self.restart_with_reason(id, config, "restart requested")
     }
 
-    pub fn apply_config_no_restart(
+    // `reason` ends up as the restartReason of the new run.
+    pub fn restart_with_reason(
         &mut self,
         id: &str,
-        _config: ServiceConfig,
+        config: ServiceConfig,
+        reason: &str,
     ) -> Result<ServiceState, String> {
-        let state = self
-            .states
-            .get(id)
-            .cloned()
-            .unwrap_or(ServiceState {
-                id: id.to_string(),
-                state: "stopped".to_string(),
-                pid: None,
-                last_error: None,
-                last_updated: now_ts(),
-            });
-        self.push_log(id, "info", "applied config without restart");
-        Ok(state)
+        let _ = self.stop(id);
+        self.shared.set_start_reason(id, reason);
+        self.start_with_config(id, config)
     }
 
-    fn start_with_dependencies(
+    pub fn apply_config_no_restart(
         &mut self,
         id: &str,
-        visiting: &mut HashSet<String>,
-    ) -> Result<ServiceState, String> {
-        self.start_with_dependencies_config(id, visiting, None)
+        config: ServiceConfig,
+    ) -> Result<ReloadOutcome, String> {
+        self.reload_with_config(id, config, false)
     }
 
-    fn start_with_dependencies_config(
+    // Re-renders config files, then reloads the running service in place.
+    // Changes a reload cannot pick up (binary, args, env, ports) restart the
+    // service when `allow_restart` is set and are reported otherwise.
+    pub fn reload_with_config(
         &mut self,
         id: &str,
-        visiting: &mut HashSet<String>,
-        config_override: Option<ServiceConfig>,
-    ) -> Result<ServiceState, String> {
-        if visiting.contains(id) {
-            return Err("dependency cycle detected".to_string());
+        config: ServiceConfig,
+        allow_restart: bool,
+    ) -> Result<ReloadOutcome, String> {
+        let mut desired = self
+            .definitions
+            .iter()
+            .find(|d| d.id == id)
+            .ok_or_else(|| format!("service not found: {id}"))?
+            .clone();
+        self.shared
+            .log_files
+            .set_policy(id, config.log_rotation.clone());
+        apply_service_config(&mut desired, &config);
+        let desired = self.shared.render(&desired)?;
+        let outcome = |outcome: &str, state: ServiceState| ReloadOutcome {
+            service: id.to_string(),
+            outcome: outcome.to_string(),
+            method: None,
+            reasons: Vec::new(),
+            rendered_files: Vec::new(),
+            state,
+        };
+
+        let current = self
+            .shared
+            .effective
+            .lock()
+            .expect("effective lock")
+            .get(id)
+            .cloned();
+        let state = self
+            .shared
+            .state(id)
+            .unwrap_or_else(|| ServiceState::new(id, "stopped", now_ts()));
+        let (Some(current), Some(pid), "running") = (current, state.pid, state.state.as_str())
+        else {
+            self.shared
+                .push_log(id, "info", "config saved; applies on next start");
+            return Ok(outcome("not-running", state));
+        };
+
+        let context = self.shared.template_context(&desired);
+        let config_dir = context.runtime_dir().join("config").join(id);
+        let paths = |paths: Vec<PathBuf>| -> Vec<String> {
+            paths
+                .iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect()
+        };
+        let mut reasons = launch_differences(&current, &desired);
+        if reasons.is_empty() && desired.reload_policy.is_none() {
+            reasons.push(format!("{id} has no reload policy"));
+        }
+
+        if !reasons.is_empty() {
+            // Files are only written when the change is applied: a restart
+            // renders them itself, and a refused change must not leave files
+            // behind for the next unrelated restart to pick up.
+            let rendered_files = paths(template::template_targets(&config_dir));
+            if allow_restart {
+                self.shared.push_log(
+                    id,
+                    "info",
+                    &format!("restarting to apply config: {}", reasons.join(", ")),
+                );
+                let reason = format!("config change: {}", reasons.join(", "));
+                let state = self.restart_with_reason(id, config, &reason)?;
+                return Ok(ReloadOutcome {
+                    reasons,
+                    rendered_files,
+                    ..outcome("restarted", state)
+                });
+            }
+            self.shared.push_log(
+                id,
+                "warn",
+                &format!("config needs a restart: {}", reasons.join(", ")),
+            );
+            return Ok(ReloadOutcome {
+                reasons,
+                rendered_files,
+                ..outcome("restart-required", state)
+            });
         }
-        visiting.insert(id.to_string());
 
+        let rendered_files = paths(template::render_files(&config_dir, &context)?);
+        let method = self.shared.reload(&desired, pid)?;
+        self.shared
+            .push_log(id, "info", &format!("reloaded config ({method})"));
+        self.shared
+            .effective
+            .lock()
+            .expect("effective lock")
+            .insert(id.to_string(), desired);
+        Ok(ReloadOutcome {
+            method: Some(method),
+            rendered_files,
+            ..outcome("reloaded", state)
+        })
+    }
+
+    // Resolves what `start_with_config` would run without spawning anything.
+    pub fn launch_plan(&self, id: &str, config: &ServiceConfig) -> Result<LaunchPlan, String> {
         let mut def = self
             .definitions
             .iter()
             .find(|d| d.id == id)
             .ok_or_else(|| format!("service not found: {id}"))?
             .clone();
+        apply_service_config(&mut def, config);
+        let context = self.shared.template_context(&def).redacting_secrets();
+        let def = context.render_definition(&def)?;
+        let mut warnings = Vec::new();
+        let (binary, binary_found) = match self.shared.runtime.resolve_binary(&def.binary) {
+            Ok(binary) => (binary, true),
+            Err(err) => {
+                warnings.push(err);
+                (self.shared.runtime.root.join(&def.binary), false)
+            }
+        };
+        let binary = std::path::absolute(&binary).unwrap_or(binary);
+        let mut env = def.env.clone();
+        env.insert("PATH".to_string(), self.shared.runtime.scoped_path(&binary));
+        let cwd = std::path::absolute(&def.cwd).unwrap_or_else(|_| PathBuf::from(&def.cwd));
+        if !cwd.is_dir() {
+            warnings.push(format!("cwd does not exist: {}", cwd.display()));
+        }
+        // A running service holds its own ports.
+        let active = matches!(
+            self.shared.state(id),
+            Some(state) if matches!(state.state.as_str(), "running" | "starting")
+        );
+        if !active {
+            for port in def.ports.iter().filter(|port| port.protocol == "tcp") {
+                if TcpListener::bind(("127.0.0.1", port.port)).is_err() {
+                    warnings.push(format!(
+                        "port {} ({}) is already in use",
+                        port.name, port.port
+                    ));
+                }
+            }
+        }
+        let config_dir = context.runtime_dir().join("config").join(id);
+        Ok(LaunchPlan {
+            service: id.to_string(),
+            binary: binary.to_string_lossy().to_string(),
+            binary_found,
+            args: def.args.clone(),
+            cwd: cwd.to_string_lossy().to_string(),
+            env,
+            ports: def.ports.clone(),
+            health_check: def.health_check.clone(),
+            depends_on: def.depends_on.clone(),
+            generated_files: template::template_targets(&config_dir)
+                .iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect(),
+            warnings,
+        })
+    }
 
+    // Dependencies first, `id` last. Every service in the plan is claimed up
+    // front; each launch worker then waits for its own dependencies.
+    pub fn start_plan(&self, id: &str) -> Result<Vec<String>, String> {
+        let mut plan = Vec::new();
+        let mut visiting = Vec::new();
+        self.visit_dependencies(id, &mut visiting, &mut plan)?;
+        Ok(plan)
+    }
+
+    fn visit_dependencies(
+        &self,
+        id: &str,
+        visiting: &mut Vec<String>,
+        plan: &mut Vec<String>,
+    ) -> Result<(), String> {
+        if plan.iter().any(|planned| planned == id) {
+            return Ok(());
+        }
+        if visiting.iter().any(|v| v == id) {
+            visiting.push(id.to_string());
+            return Err(format!(
+                "dependency cycle detected: {}",
+                visiting.join(" -> ")
+            ));
+        }
+        let def = self
+            .definitions
+            .iter()
+            .find(|d| d.id == id)
+            .ok_or_else(|| match visiting.last() {
+                Some(parent) => format!("service not found: {id} (dependency of {parent})"),
+                None => format!("service not found: {id}"),
+            })?;
+        visiting.push(id.to_string());
         for dep in &def.depends_on {
-            let _ = self.start_with_dependencies_config(dep, visiting, None);
+            self.visit_dependencies(dep, visiting, plan)?;
         }
+        visiting.pop();
+        plan.push(id.to_string());
+        Ok(())
+    }
 
-        if let Some(state) = self.states.get(id) {
-            if state.state == "running" {
-                visiting.remove(id);
-                return Ok(state.clone());
+    // Services that depend on `id`, directly or transitively, ordered so each
+    // one comes before anything it depends on (safe stop order).
+    pub fn dependents(&self, id: &str) -> Vec<String> {
+        let mut order = Vec::new();
+        for def in &self.definitions {
+            let mut visiting = Vec::new();
+            let _ = self.visit_dependencies(&def.id, &mut visiting, &mut order);
+        }
+        let mut affected: HashSet<String> = HashSet::new();
+        affected.insert(id.to_string());
+        let mut dependents = Vec::new();
+        for service in &order {
+            let def = match self.definitions.iter().find(|d| &d.id == service) {
+                Some(def) => def,
+                None => continue,
+            };
+            if def.depends_on.iter().any(|dep| affected.contains(dep)) {
+                affected.insert(service.clone());
+                dependents.push(service.clone());
             }
         }
+        dependents.reverse();
+        dependents
+    }
+
+    fn start_planned(
+        &mut self,
+        id: &str,
+        config_override: Option<ServiceConfig>,
+    ) -> Result<ServiceState, String> {
+        let plan = self.start_plan(id)?;
+        let mut def = self
+            .definitions
+            .iter()
+            .find(|d| d.id == id)
+            .ok_or_else(|| format!("service not found: {id}"))?
+            .clone();
 
         if let Some(config) = config_override {
             if !config.enabled {
-                visiting.remove(id);
                 return Err("service disabled".to_string());
             }
-            for port in def.ports.iter_mut() {
-                if let Some(value) = config.ports.get(&port.name) {
-                    port.port = *value;
-                }
-            }
-            for (key, value) in config.env {
-                def.env.insert(key, value);
-            }
-            for arg in config.args {
-                def.args.push(arg);
-            }
-            // Override binary if version is specified
-            if let Some(ver) = &config.version {
-                if !ver.is_empty() {
-                    let new_bin_path = runtime::bin_path_for(&def.id, ver);
-                    // Check if it exists, otherwise fallback or fail?
-                    // We assume it exists if user selected it.
-                    // But we need to resolve it relative to root.
-                    // Since runtime.resolve_binary does lookup, we just need to pass the relative path string
-                    // But wait, resolve_binary expects configured binary path from definition.
-                    // We should update def.binary.
-                    def.binary = new_bin_path;
-                }
+            self.shared
+                .log_files
+                .set_policy(&def.id, config.log_rotation.clone());
+            apply_service_config(&mut def, &config);
+            self.shared
+                .push_log(&def.id, "info", "applied service config");
+        }
+
+        for dep in plan.iter().filter(|planned| *planned != id) {
+            if let Some(dep_def) = self.definitions.iter().find(|d| &d.id == dep).cloned() {
+                self.shared
+                    .start_reasons
+                    .lock()
+                    .expect("start reasons lock")
+                    .entry(dep.clone())
+                    .or_insert_with(|| format!("dependency of {id}"));
+                self.begin_start(dep_def);
             }
-            self.push_log(&def.id, "info", "applied service config");
         }
+        Ok(self.begin_start(def))
+    }
 
-        let binary = match self.runtime.resolve_binary(&def.binary) {
-            Ok(path) => path,
-            Err(err) => {
-                if let Some(version) = runtime::default_versions().get(&def.id).cloned() {
-                    let _ = self.runtime.ensure_service(&def.id, &version);
-                }
-                match self.runtime.resolve_binary(&def.binary) {
-                    Ok(path) => path,
-                    Err(_) => {
-                        visiting.remove(id);
-                        return Err(err);
-                    }
+    fn begin_start(&mut self, def: ServiceDefinition) -> ServiceState {
+        self.shared.begin_start(def)
+    }
+
+    // Brings the running set to exactly `members` plus their dependencies:
+    // everything else is stopped (dependents first), then members start in
+    // dependency order. A running member is restarted only when its config
+    // differs from what it was launched with.
+    pub fn activate_profile(
+        &mut self,
+        members: Vec<ServiceConfig>,
+    ) -> Result<Vec<ServiceState>, String> {
+        let mut keep: Vec<String> = Vec::new();
+        for member in &members {
+            for id in self.start_plan(&member.id)? {
+                if !keep.contains(&id) {
+                    keep.push(id);
                 }
             }
-        };
-        self.ensure_service_data(&def, &binary);
-        let starting = ServiceState {
-            id: def.id.clone(),
-            state: "starting".to_string(),
-            pid: None,
-            last_error: None,
-            last_updated: now_ts(),
-        };
-        self.states.insert(def.id.clone(), starting);
-        let mut cmd = Command::new(&binary);
-        cmd.args(&def.args)
-            .current_dir(&def.cwd)
-            .envs(&def.env)
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-        let path_value = self.runtime.scoped_path(&binary);
-        cmd.env("PATH", path_value);
+        }
 
-        let mut child = match cmd.spawn() {
-            Ok(child) => child,
-            Err(err) => {
-                visiting.remove(id);
-                return Err(err.to_string());
+        let ids: Vec<String> = self.definitions.iter().map(|d| d.id.clone()).collect();
+        for id in ids.iter().filter(|id| !keep.contains(id)) {
+            let active = matches!(self.shared.state(id), Some(state) if state.state != "stopped");
+            if active {
+                self.stop_with_dependents(id)?;
             }
-        };
-        let pid = Some(child.id());
-        self.capture_logs(&def.id, &mut child);
-        self.processes.insert(def.id.clone(), child);
+        }
 
-        let health = self.check_health_with_retries(&def);
-        let (state_value, last_error) = match health {
-            Ok(()) => ("running".to_string(), None),
-            Err(err) => ("error".to_string(), Some(err)),
+        let mut states = Vec::new();
+        for id in &keep {
+            let config = match members.iter().find(|member| &member.id == id) {
+                Some(config) => config.clone(),
+                None => continue,
+            };
+            let running =
+                matches!(self.shared.state(id), Some(state) if state.state == "running");
+            let state = if running && !self.launched_with(id, &config) {
+                self.restart_with_reason(id, config, "profile activated with a different config")?
+            } else {
+                self.start_with_config(id, config)?
+            };
+            states.push(state);
+        }
+        Ok(states)
+    }
+
+    fn launched_with(&self, id: &str, config: &ServiceConfig) -> bool {
+        let effective = self.shared.effective.lock().expect("effective lock");
+        let (Some(current), Some(base)) = (
+            effective.get(id),
+            self.definitions.iter().find(|d| d.id == id),
+        ) else {
+            return true;
         };
-        let state = ServiceState {
-            id: def.id.clone(),
-            state: state_value,
-            pid,
-            last_error,
-            last_updated: now_ts(),
+        let mut desired = base.clone();
+        apply_service_config(&mut desired, config);
+        let ports = |def: &ServiceDefinition| -> Vec<(String, u16)> {
+            def.ports.iter().map(|p| (p.name.clone(), p.port)).collect()
         };
-        self.states.insert(def.id.clone(), state.clone());
-        if state.state == "running" {
-            self.restart_attempts.insert(def.id.clone(), 0);
+        current.binary == desired.binary
+            && current.args == desired.args
+            && current.env == desired.env
+            && ports(current) == ports(&desired)
+    }
+
+    pub fn stop_with_dependents(&mut self, id: &str) -> Result<ServiceState, String> {
+        // Each stop waits for the ones before it, so dependents go down first.
+        let mut pending = Vec::new();
+        for dependent in self.dependents(id) {
+            let active =
+                matches!(self.shared.state(&dependent), Some(state) if state.state != "stopped");
+            if active {
+                let (_, done) = self.stop_after(&dependent, pending.clone())?;
+                pending.extend(done);
+            }
         }
-        self.push_log(&def.id, "info", "service started");
-        visiting.remove(id);
-        Ok(state)
+        self.stop_after(id, pending).map(|(state, _)| state)
     }
 
+    // Marks the service stopping and leaves the hooks and the stop policy to
+    // a worker, like a launch; `wait_for` returns once it has stopped.
     pub fn stop(&mut self, id: &str) -> Result<ServiceState, String> {
-        let child = self.processes.get_mut(id);
-        if let Some(child) = child {
-            child.kill().map_err(|e| e.to_string())?;
-            let _ = child.wait();
+        self.stop_after(id, Vec::new()).map(|(state, _)| state)
+    }
+
+    fn stop_after(
+        &mut self,
+        id: &str,
+        after: Vec<Arc<StopDone>>,
+    ) -> Result<(ServiceState, Option<Arc<StopDone>>), String> {
+        // Dropping the launch id cancels a start that is still in flight, and
+        // dropping the crash state cancels a scheduled restart.
+        self.shared
+            .launches
+            .lock()
+            .expect("launches lock")
+            .remove(id);
+        self.shared.reset_crashes(id);
+        let pending = self.shared.stops.lock().expect("stops lock").get(id).cloned();
+        if let Some(pending) = pending {
+            let state = match self.shared.state(id) {
+                Some(state) if state.state == "stopping" => state,
+                _ => {
+                    let state = ServiceState::new(id, "stopping", now_ts());
+                    self.shared.set_state(state.clone());
+                    state
+                }
+            };
+            return Ok((state, Some(pending)));
         }
-        self.processes.remove(id);
+        let child = self
+            .shared
+            .processes
+            .lock()
+            .expect("processes lock")
+            .remove(id);
+        let adopted = self.shared.adopted.lock().expect("adopted lock").remove(id);
+        let pid = match (&child, adopted) {
+            (Some(child), _) => child.id(),
+            (None, Some(pid)) => pid,
+            (None, None) => {
+                let state = ServiceState::new(id, "stopped", now_ts());
+                self.shared.set_state(state.clone());
+                self.shared.push_log(id, "info", "service stopped");
+                return Ok((state, None));
+            }
+        };
 
-        let state = ServiceState {
-            id: id.to_string(),
-            state: "stopped".to_string(),
-            pid: None,
-            last_error: None,
-            last_updated: now_ts(),
+        let stopping = ServiceState {
+            pid: Some(pid),
+            ..ServiceState::new(id, "stopping", now_ts())
         };
-        self.states.insert(id.to_string(), state.clone());
-        self.push_log(id, "info", "service stopped");
-        Ok(state)
+        self.shared.set_state(stopping.clone());
+        let def = self
+            .shared
+            .effective
+            .lock()
+            .expect("effective lock")
+            .remove(id)
+            .or_else(|| self.definitions.iter().find(|d| d.id == id).cloned());
+        let done = Arc::new(StopDone::default());
+        self.shared
+            .stops
+            .lock()
+            .expect("stops lock")
+            .insert(id.to_string(), Arc::clone(&done));
+        let shared = self.shared.clone();
+        let worker_done = Arc::clone(&done);
+        let id = id.to_string();
+        thread::spawn(move || {
+            for earlier in &after {
+                earlier.wait();
+            }
+            shared.finish_stop(&id, def, child, adopted);
+            let mut stops = shared.stops.lock().expect("stops lock");
+            if stops.get(&id).is_some_and(|current| Arc::ptr_eq(current, &worker_done)) {
+                stops.remove(&id);
+            }
+            drop(stops);
+            worker_done.finish();
+        });
+        Ok((stopping, Some(done)))
     }
 
     pub fn restart(&mut self, id: &str) -> Result<ServiceState, String> {
-        self.states.insert(
-            id.to_string(),
-            ServiceState {
-                id: id.to_string(),
-                state: "restarting".to_string(),
-                pid: None,
-                last_error: None,
-                last_updated: now_ts(),
-            },
-        );
+        self.shared
+            .set_state(ServiceState::new(id, "restarting", now_ts()));
         let _ = self.stop(id);
+        self.shared.set_start_reason(id, "restart requested");
         self.start(id)
     }
 
     pub fn tick(&mut self) {
+        self.begin_tick().run();
+    }
+
+    // The bookkeeping part of a tick. The returned probes can take up to
+    // their timeouts, so callers holding a lock around the manager run them
+    // after releasing it.
+    pub fn begin_tick(&mut self) -> HealthRound {
         self.poll_process_exits();
-        self.refresh_health();
+        self.refresh_lineage();
+        self.health_round()
+    }
+
+    // Descendants that outlived their service and could not be cleaned up.
+    pub fn leaked_processes(&self) -> Vec<LeakedProcess> {
+        let mut leaked = self.shared.leaked.lock().expect("leaked lock");
+        leaked.retain(|entry| {
+            process::is_same_process(&ProcessRef {
+                pid: entry.pid,
+                started_at: entry.started_at,
+                name: entry.name.clone(),
+            })
+        });
+        leaked.clone()
+    }
+
+    pub fn kill_leaked(&mut self) -> Vec<LeakedProcess> {
+        let leaked = self.leaked_processes();
+        let refs: Vec<ProcessRef> = leaked
+            .iter()
+            .map(|entry| ProcessRef {
+                pid: entry.pid,
+                started_at: entry.started_at,
+                name: entry.name.clone(),
+            })
+            .collect();
+        process::kill_processes(&refs);
+        self.leaked_processes()
+    }
+
+    // (service id, root pid) for every process the manager is responsible for.
+    pub fn tracked_pids(&self) -> Vec<(String, u32)> {
+        let mut roots: Vec<(String, u32)> = self
+            .shared
+            .processes
+            .lock()
+            .expect("processes lock")
+            .iter()
+            .map(|(id, child)| (id.clone(), child.id()))
+            .collect();
+        roots.extend(
+            self.shared
+                .adopted
+                .lock()
+                .expect("adopted lock")
+                .iter()
+                .map(|(id, pid)| (id.clone(), *pid)),
+        );
+        roots
+    }
+
+    fn refresh_lineage(&mut self) {
+        let roots = self.tracked_pids();
+        let mut lineage = self.shared.lineage.lock().expect("lineage lock");
+        lineage.retain(|id, _| roots.iter().any(|(root, _)| root == id));
+        for (id, pid) in roots {
+            lineage.insert(id, process::descendants(pid));
+        }
+    }
+
+    // The tail plus the seq of the newest line pushed so far, read together.
+    pub fn log_snapshot(&self, id: &str, tail: usize) -> LogSnapshot {
+        let logs = self.shared.logs.lock().expect("logs lock");
+        let last_seq = self
+            .shared
+            .log_seq
+            .lock()
+            .expect("log seq lock")
+            .get(id)
+            .copied()
+            .unwrap_or(0);
+        let buffer = logs.get(id).map(Vec::as_slice).unwrap_or_default();
+        LogSnapshot {
+            entries: buffer[buffer.len().saturating_sub(tail)..].to_vec(),
+            last_seq,
+        }
     }
 
     pub fn logs(&self, id: &str, tail: usize) -> Vec<LogEntry> {
         let logs = self
+            .shared
             .logs
             .lock()
             .expect("logs lock")
@@ -296,11 +867,22 @@ impl ServiceManager {
     }
 
     pub fn log_path(&self, id: &str) -> String {
-        self.log_root.join(format!("{id}.log")).to_string_lossy().to_string()
+        self.shared
+            .log_files
+            .active_path(id)
+            .to_string_lossy()
+            .to_string()
     }
 
     pub fn snapshot_logs(&self) -> HashMap<String, Vec<LogEntry>> {
-        self.logs.lock().expect("logs lock").clone()
+        self.shared.logs.lock().expect("logs lock").clone()
+    }
+
+    pub fn query_logs(&self, query: &LogQuery) -> Result<LogPage, String> {
+        // Files are read after the snapshot so the logs lock is not held during I/O.
+        let memory = self.snapshot_logs();
+        self.shared.log_files.flush_all();
+        log_query::query(self.shared.log_files.root(), memory, query)
     }
 
     pub fn export_logs(
@@ -326,7 +908,8 @@ impl ServiceManager {
                 entries.push(entry);
             }
         }
-        entries.sort_by(|a, b| a.ts.cmp(&b.ts));
+        // Fixed-width RFC 3339 timestamps sort lexically; seq breaks ties.
+        entries.sort_by(|a, b| a.ts.cmp(&b.ts).then(a.seq.cmp(&b.seq)));
         let cap = if limit == 0 { 200 } else { limit };
         let slice = if entries.len() > cap {
             entries[entries.len() - cap..].to_vec()
@@ -334,11 +917,8 @@ impl ServiceManager {
             entries
         };
 
-        let export_dir = self
-            .log_root
-            .parent()
-            .unwrap_or(&self.log_root)
-            .join("exports");
+        let log_root = self.shared.log_files.root();
+        let export_dir = log_root.parent().unwrap_or(log_root).join("exports");
         std::fs::create_dir_all(&export_dir).map_err(|e| e.to_string())?;
         let service_tag = service.unwrap_or("all");
         let level_tag = level.unwrap_or("all");
@@ -356,149 +936,1272 @@ impl ServiceManager {
     }
 
     pub fn clear_logs(&self, service_id: Option<&str>) -> Result<(), String> {
-        let mut logs = self.logs.lock().expect("logs lock");
-        if let Some(id) = service_id {
-            if let Some(buffer) = logs.get_mut(id) {
-                buffer.clear();
-            }
-            let path = self.log_root.join(format!("{id}.log"));
-            if path.exists() {
-                std::fs::write(&path, "").map_err(|e| e.to_string())?;
-            }
-        } else {
-            logs.clear();
-            if let Ok(entries) = std::fs::read_dir(&self.log_root) {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if path.extension().map_or(false, |e| e == "log") {
-                        let _ = std::fs::write(&path, "");
+        {
+            let mut logs = self.shared.logs.lock().expect("logs lock");
+            match service_id {
+                Some(id) => {
+                    if let Some(buffer) = logs.get_mut(id) {
+                        buffer.clear();
                     }
                 }
+                None => logs.clear(),
             }
         }
-        Ok(())
+        match service_id {
+            Some(id) => self.shared.log_files.clear(id),
+            None => self.shared.log_files.clear_all(),
+        }
     }
 
-    fn push_log(&self, id: &str, level: &str, message: &str) {
-        let entry = LogEntry {
-            ts: now_ts(),
-            level: level.to_string(),
-            service: id.to_string(),
-            message: message.to_string(),
-            fields: HashMap::new(),
-        };
-        let mut logs = self.logs.lock().expect("logs lock");
-        let buffer = logs.entry(id.to_string()).or_default();
-        buffer.push(entry);
-        if buffer.len() > self.log_limit {
-            let extra = buffer.len() - self.log_limit;
-            buffer.drain(0..extra);
-        }
-        let _ = write_log_line(&self.log_root, id, level, message);
+    pub fn set_log_rotation(&self, id: &str, policy: LogRotation) {
+        self.shared.log_files.set_policy(id, policy);
     }
 
-    fn capture_logs(&self, id: &str, child: &mut Child) {
-        if let Some(stdout) = child.stdout.take() {
-            let logs = self.logs.clone();
-            let log_root = self.log_root.clone();
-            let id = id.to_string();
-            thread::spawn(move || {
-                let reader = BufReader::new(stdout);
-                for line in reader.lines().flatten() {
-                    push_log_shared(&logs, &log_root, &id, "info", &line, 2000);
-                }
-            });
-        }
+    // Handle for the log janitor, which runs without the manager lock.
+    pub fn log_files(&self) -> LogFiles {
+        self.shared.log_files.clone()
+    }
 
-        if let Some(stderr) = child.stderr.take() {
-            let logs = self.logs.clone();
-            let log_root = self.log_root.clone();
-            let id = id.to_string();
-            thread::spawn(move || {
-                let reader = BufReader::new(stderr);
-                for line in reader.lines().flatten() {
-                    push_log_shared(&logs, &log_root, &id, "error", &line, 2000);
-                }
-            });
+    // Probes with the ports the service was started with, if it is running.
+    pub fn health(&self, id: &str) -> Result<String, String> {
+        self.health_task(id)?.run()
+    }
+
+    // Resolves what `health` would probe without probing yet.
+    pub fn health_task(&self, id: &str) -> Result<HealthTask, String> {
+        let effective = self
+            .shared
+            .effective
+            .lock()
+            .expect("effective lock")
+            .get(id)
+            .cloned();
+        let def = match effective {
+            Some(def) => def,
+            None => self
+                .definitions
+                .iter()
+                .find(|d| d.id == id)
+                .ok_or_else(|| format!("service not found: {id}"))
+                .and_then(|def| self.shared.render(def))?,
+        };
+        Ok(HealthTask {
+            shared: self.shared.clone(),
+            def,
+        })
+    }
+
+    // Probe history with stats over each window; probes only happen while
+    // the service is running, so stopped time does not count as downtime.
+    // Up to `probe_limit` of the newest raw probes come along.
+    pub fn health_history(
+        &self,
+        id: &str,
+        windows_secs: &[u64],
+        probe_limit: usize,
+    ) -> Result<HealthReport, String> {
+        if !self.definitions.iter().any(|def| def.id == id) {
+            return Err(format!("service not found: {id}"));
         }
+        Ok(self
+            .shared
+            .health_history
+            .report(id, now_secs(), windows_secs, probe_limit))
     }
 
-    fn ensure_service_data(&self, def: &ServiceDefinition, binary: &PathBuf) {
-        if def.id == "postgres" {
-            let data_dir = def
-                .env
-                .get("PGDATA")
+    fn health_round(&self) -> HealthRound {
+        // Services still in "starting" belong to their launch worker. Errored
+        // services whose process is alive are probed so they can recover.
+        let candidates: Vec<ServiceState> = {
+            let processes = self.shared.processes.lock().expect("processes lock");
+            self.shared
+                .states
+                .lock()
+                .expect("states lock")
+                .values()
+                .filter(|state| {
+                    state.state == "running"
+                        || (state.state == "error" && processes.contains_key(&state.id))
+                })
                 .cloned()
-                .unwrap_or_else(|| "runtime/data/postgres".to_string());
-            let data_path = PathBuf::from(&data_dir);
-            let _ = std::fs::create_dir_all(&data_path);
-            let marker = data_path.join("PG_VERSION");
-            if marker.exists() {
-                return;
-            }
-            let initdb = binary
-                .parent()
-                .map(|parent| parent.join(if cfg!(target_os = "windows") { "initdb.exe" } else { "initdb" }))
-                .unwrap_or_else(|| PathBuf::from("initdb"));
-            if initdb.exists() {
-                let _ = Command::new(initdb)
-                    .arg("-D")
-                    .arg(&data_dir)
-                    .stdout(Stdio::null())
-                    .stderr(Stdio::null())
-                    .status();
-            } else {
-                self.push_log(&def.id, "error", "initdb not found for postgres");
-            }
+                .collect()
+        };
+        let effective = self.shared.effective.lock().expect("effective lock").clone();
+        let probes = candidates
+            .into_iter()
+            .filter_map(|state| {
+                let def = effective
+                    .get(&state.id)
+                    .cloned()
+                    .or_else(|| self.definitions.iter().find(|d| d.id == state.id).cloned())?;
+                Some((state, def))
+            })
+            .collect();
+        HealthRound {
+            shared: self.shared.clone(),
+            probes,
         }
-        if def.id == "mariadb" {
-            let data_dir = "runtime/data/mariadb".to_string();
-            let data_path = PathBuf::from(&data_dir);
-            let _ = std::fs::create_dir_all(&data_path);
-            let marker = data_path.join("mysql");
-            if marker.exists() {
+    }
+
+    fn poll_process_exits(&mut self) {
+        let exited: Vec<(String, bool, Option<i32>, Option<i32>)> = {
+            let mut processes = self.shared.processes.lock().expect("processes lock");
+            let mut exited = Vec::new();
+            for (id, child) in processes.iter_mut() {
+                if let Ok(Some(status)) = child.try_wait() {
+                    exited.push((
+                        id.clone(),
+                        status.success(),
+                        status.code(),
+                        process::exit_signal(&status),
+                    ));
+                }
+            }
+            for (id, _, _, _) in &exited {
+                if let Some(child) = processes.remove(id) {
+                    process::release(child.id());
+                }
+            }
+            // Adopted processes are not our children, so there is no exit status.
+            let mut adopted = self.shared.adopted.lock().expect("adopted lock");
+            let gone: Vec<String> = adopted
+                .iter()
+                .filter(|(_, pid)| !process::is_alive(**pid))
+                .map(|(id, _)| id.clone())
+                .collect();
+            for id in gone {
+                adopted.remove(&id);
+                exited.push((id, false, None, None));
+            }
+            exited
+        };
+
+        for (id, success, exit_code, signal) in exited {
+            self.shared.forget_pid(&id);
+            self.shared.finish_capture(&id);
+            let orphans: Vec<ProcessRef> = self
+                .shared
+                .lineage
+                .lock()
+                .expect("lineage lock")
+                .remove(&id)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(process::is_same_process)
+                .collect();
+            self.shared.record_leaks(&id, &orphans);
+            let effective = self
+                .shared
+                .effective
+                .lock()
+                .expect("effective lock")
+                .remove(&id);
+            self.shared
+                .healthy
+                .lock()
+                .expect("healthy lock")
+                .remove(&id);
+            if !success {
+                self.shared.events.emit(ServiceEvent::Crashed {
+                    service: id.clone(),
+                    exit_code,
+                });
+            }
+            let level = if success { "info" } else { "error" };
+            let description = exit_description(exit_code, signal);
+            self.shared.push_log(&id, level, &description);
+            let stderr_tail = self.shared.stderr_tail(&id);
+            let outcome = if success { "exited" } else { "crashed" };
+            self.shared.close_run(&id, outcome, |run| {
+                run.exit_code = exit_code;
+                run.signal = signal;
+                run.stderr_tail = stderr_tail;
+            });
+            if success {
+                self.shared.reset_crashes(&id);
+                self.shared
+                    .set_state(ServiceState::new(&id, "stopped", now_ts()));
+                continue;
+            }
+            // Restarts reuse the config the crashed process was started with.
+            let def = effective.or_else(|| self.definitions.iter().find(|d| d.id == id).cloned());
+            match def {
+                Some(def) => self.shared.handle_crash(def, exit_code, signal),
+                None => self.shared.set_state(ServiceState {
+                    last_error: Some(description),
+                    last_exit_code: exit_code,
+                    last_signal: signal,
+                    ..ServiceState::new(&id, "error", now_ts())
+                }),
+            }
+        }
+    }
+}
+
+fn exit_description(exit_code: Option<i32>, signal: Option<i32>) -> String {
+    match (exit_code, signal) {
+        (Some(code), _) => format!("process exited with code {code}"),
+        (None, Some(signal)) => format!("process killed by signal {signal}"),
+        (None, None) => "process exited".to_string(),
+    }
+}
+
+// What differs between two rendered definitions in ways only a new process
+// can pick up.
+fn launch_differences(current: &ServiceDefinition, desired: &ServiceDefinition) -> Vec<String> {
+    let mut reasons = Vec::new();
+    for port in &desired.ports {
+        match current.ports.iter().find(|p| p.name == port.name) {
+            Some(old) if old.port != port.port => reasons.push(format!(
+                "port {} changed: {} -> {}",
+                port.name, old.port, port.port
+            )),
+            None => reasons.push(format!("port {} added", port.name)),
+            _ => {}
+        }
+    }
+    if current.binary != desired.binary {
+        reasons.push("binary changed".to_string());
+    }
+    if current.args != desired.args {
+        reasons.push("args changed".to_string());
+    }
+    if current.cwd != desired.cwd {
+        reasons.push("cwd changed".to_string());
+    }
+    let mut env_keys: Vec<&String> = current
+        .env
+        .keys()
+        .chain(desired.env.keys())
+        .filter(|key| current.env.get(*key) != desired.env.get(*key))
+        .collect();
+    env_keys.sort();
+    env_keys.dedup();
+    if !env_keys.is_empty() {
+        let keys: Vec<&str> = env_keys.iter().map(|key| key.as_str()).collect();
+        reasons.push(format!("env changed: {}", keys.join(", ")));
+    }
+    reasons
+}
+
+fn apply_service_config(def: &mut ServiceDefinition, config: &ServiceConfig) {
+    for port in def.ports.iter_mut() {
+        if let Some(value) = config.ports.get(&port.name) {
+            port.port = *value;
+        }
+    }
+    for (key, value) in &config.env {
+        def.env.insert(key.clone(), value.clone());
+    }
+    def.args.extend(config.args.iter().cloned());
+    // A pinned version runs the binary from runtime/bin/{id}/{version};
+    // resolve_binary turns the relative path into an absolute one at launch.
+    if let Some(version) = &config.version {
+        if !version.is_empty() {
+            def.binary = runtime::bin_path_for(&def.id, version);
+            def.version = Some(version.clone());
+        }
+    }
+}
+
+// Exponential with equal jitter: half the delay is fixed, half is random.
+fn restart_delay(policy: &RestartPolicy, crashes: u32) -> Duration {
+    let exponent = crashes.saturating_sub(1).min(20);
+    let delay = policy
+        .backoff_ms
+        .saturating_mul(1 << exponent)
+        .min(policy.max_backoff_ms.max(policy.backoff_ms));
+    let half = delay / 2;
+    Duration::from_millis(half + rand::random::<u64>() % (half + 1))
+}
+
+impl Shared {
+    // The part of a stop that runs on a worker: hooks, the stop policy and
+    // the bookkeeping once the process is gone.
+    fn finish_stop(
+        &self,
+        id: &str,
+        def: Option<ServiceDefinition>,
+        mut child: Option<Child>,
+        adopted: Option<u32>,
+    ) {
+        let target = match (child.as_mut(), adopted) {
+            (Some(child), _) => Tracked::Child(child),
+            (None, Some(pid)) => Tracked::Adopted(pid),
+            (None, None) => return,
+        };
+        let pid = target.pid();
+        // Stop hooks never keep the service running; failures are logged.
+        if let Some(def) = &def {
+            let _ = self.run_hooks(def, "pre-stop", &def.hooks.pre_stop);
+        }
+        let method = match self.shutdown(id, def.as_ref(), target) {
+            Ok(method) => method,
+            Err(err) => {
+                let err = format!("stop failed: {err}");
+                self.push_log(id, "error", &err);
+                self.set_state(ServiceState {
+                    pid: Some(pid),
+                    last_error: Some(err),
+                    ..ServiceState::new(id, "error", now_ts())
+                });
                 return;
             }
-            let installer = binary
-                .parent()
-                .map(|parent| {
-                    parent.join(if cfg!(target_os = "windows") {
-                        "mariadb-install-db.exe"
-                    } else {
-                        "mariadb-install-db"
-                    })
-                })
-                .unwrap_or_else(|| PathBuf::from("mariadb-install-db"));
-            if installer.exists() {
-                let _ = Command::new(installer)
-                    .arg(format!("--datadir={data_dir}"))
-                    .stdout(Stdio::null())
-                    .stderr(Stdio::null())
-                    .status();
+        };
+        self.forget_pid(id);
+        self.finish_capture(id);
+        self.close_run(id, "stopped", |run| {
+            run.stop_method = Some(method.clone());
+        });
+        if let Some(def) = &def {
+            let _ = self.run_hooks(def, "post-stop", &def.hooks.post_stop);
+        }
+        self.push_log(id, "info", &format!("service stopped ({method})"));
+        // A start requested while this stop ran owns the state by now.
+        let launches = self.launches.lock().expect("launches lock");
+        if !launches.contains_key(id) {
+            self.set_state(ServiceState {
+                stop_method: Some(method),
+                ..ServiceState::new(id, "stopped", now_ts())
+            });
+        }
+    }
+
+    fn wait_for_stop(&self, id: &str) {
+        let pending = self.stops.lock().expect("stops lock").get(id).cloned();
+        if let Some(pending) = pending {
+            pending.wait();
+        }
+    }
+
+    fn begin_start(&self, def: ServiceDefinition) -> ServiceState {
+        // A start that is already in flight owns the service; starting it
+        // again would spawn a second process.
+        let reason = self
+            .start_reasons
+            .lock()
+            .expect("start reasons lock")
+            .remove(&def.id);
+        if let Some(state) = self.state(&def.id) {
+            if state.state == "running" || state.state == "starting" {
+                return state;
+            }
+        }
+
+        let starting = ServiceState::new(&def.id, "starting", now_ts());
+        let launch = self.next_launch.fetch_add(1, Ordering::SeqCst);
+        {
+            // Under the launches lock, so a stop worker finishing now cannot
+            // mark the service stopped after this.
+            let mut launches = self.launches.lock().expect("launches lock");
+            launches.insert(def.id.clone(), launch);
+            self.set_state(starting.clone());
+        }
+
+        let shared = self.clone();
+        thread::spawn(move || shared.launch(def, launch, reason));
+        starting
+    }
+
+    fn handle_crash(&self, def: ServiceDefinition, exit_code: Option<i32>, signal: Option<i32>) {
+        let id = def.id.as_str();
+        let policy = &def.restart_policy;
+        let crashes = {
+            let mut crashes = self.crashes.lock().expect("crashes lock");
+            let history = crashes.entry(id.to_string()).or_default();
+            let now = Instant::now();
+            let window = Duration::from_millis(policy.window_ms);
+            while matches!(history.front(), Some(at) if now.duration_since(*at) > window) {
+                history.pop_front();
+            }
+            history.push_back(now);
+            history.len() as u32
+        };
+        let stderr_tail = self.stderr_tail(id);
+        let exited = exit_description(exit_code, signal);
+        let crashed = |state: &str, last_error: String| ServiceState {
+            last_error: Some(last_error),
+            last_exit_code: exit_code,
+            last_signal: signal,
+            stderr_tail: stderr_tail.clone(),
+            ..ServiceState::new(id, state, now_ts())
+        };
+
+        if policy.max_retries == 0 {
+            self.set_state(crashed("error", exited));
+            return;
+        }
+        if crashes > policy.max_retries {
+            let message = format!(
+                "crash loop: {crashes} crashes within {}s; {exited}",
+                policy.window_ms / 1000
+            );
+            self.push_log(id, "error", &message);
+            self.set_state(crashed("crash-loop", message));
+            return;
+        }
+
+        let delay = restart_delay(policy, crashes);
+        let token = self.next_launch.fetch_add(1, Ordering::SeqCst);
+        self.pending_restarts
+            .lock()
+            .expect("pending restarts lock")
+            .insert(id.to_string(), token);
+        let reason = format!("crash: {exited} (attempt {crashes})");
+        self.set_state(crashed("restarting", exited));
+        self.push_log(
+            id,
+            "info",
+            &format!("restarting in {}ms (attempt {crashes})", delay.as_millis()),
+        );
+        let shared = self.clone();
+        thread::spawn(move || {
+            thread::sleep(delay);
+            let current = {
+                let mut pending = shared
+                    .pending_restarts
+                    .lock()
+                    .expect("pending restarts lock");
+                if pending.get(&def.id) == Some(&token) {
+                    pending.remove(&def.id);
+                    true
+                } else {
+                    false
+                }
+            };
+            if current {
+                let service = def.id.clone();
+                shared.set_start_reason(&service, &reason);
+                shared.begin_start(def);
+                shared.events.emit(ServiceEvent::Restarted {
+                    service,
+                    attempt: crashes,
+                });
+            }
+        });
+    }
+
+    fn set_start_reason(&self, id: &str, reason: &str) {
+        self.start_reasons
+            .lock()
+            .expect("start reasons lock")
+            .insert(id.to_string(), reason.to_string());
+    }
+
+    fn reset_crashes(&self, id: &str) {
+        self.crashes.lock().expect("crashes lock").remove(id);
+        self.pending_restarts
+            .lock()
+            .expect("pending restarts lock")
+            .remove(id);
+    }
+
+    fn state(&self, id: &str) -> Option<ServiceState> {
+        self.states.lock().expect("states lock").get(id).cloned()
+    }
+
+    fn set_state(&self, state: ServiceState) {
+        let previous = self
+            .states
+            .lock()
+            .expect("states lock")
+            .insert(state.id.clone(), state.clone());
+        let unchanged = matches!(
+            &previous,
+            Some(prev) if prev.state == state.state && prev.pid == state.pid
+        );
+        if !unchanged {
+            self.events.emit(ServiceEvent::StateChanged {
+                previous: previous.map(|prev| prev.state),
+                state,
+            });
+        }
+    }
+
+    // Records a periodic probe of `probed`, unless the service was stopped or
+    // relaunched while the probe ran.
+    fn apply_probe(&self, probed: &ServiceState, started: Instant, result: Result<(), String>) {
+        let current = self
+            .states
+            .lock()
+            .expect("states lock")
+            .get(&probed.id)
+            .cloned();
+        let unchanged = current.is_some_and(|current| {
+            current.pid == probed.pid && (current.state == "running" || current.state == "error")
+        });
+        if !unchanged {
+            return;
+        }
+        self.health_history.record(
+            &probed.id,
+            HealthProbe {
+                ts: now_secs(),
+                latency_ms: started.elapsed().as_millis() as u64,
+                healthy: result.is_ok(),
+                error: result.clone().err(),
+            },
+        );
+        self.record_health(&probed.id, &result);
+        match result {
+            Ok(()) => {
+                if probed.state != "running" {
+                    self.set_state(ServiceState {
+                        pid: probed.pid,
+                        ..ServiceState::new(&probed.id, "running", now_ts())
+                    });
+                }
+            }
+            Err(err) => {
+                if probed.state != "error" || probed.last_error.as_ref() != Some(&err) {
+                    self.set_state(ServiceState {
+                        pid: probed.pid,
+                        last_error: Some(err),
+                        ..ServiceState::new(&probed.id, "error", now_ts())
+                    });
+                }
+            }
+        }
+    }
+
+    fn record_health(&self, id: &str, result: &Result<(), String>) {
+        let healthy = result.is_ok();
+        let previous = self
+            .healthy
+            .lock()
+            .expect("healthy lock")
+            .insert(id.to_string(), healthy);
+        if previous != Some(healthy) {
+            self.events.emit(ServiceEvent::Health {
+                service: id.to_string(),
+                healthy,
+                error: result.clone().err(),
+            });
+        }
+    }
+
+    fn is_current(&self, id: &str, launch: u64) -> bool {
+        self.launches.lock().expect("launches lock").get(id) == Some(&launch)
+    }
+
+    fn launch(&self, def: ServiceDefinition, launch: u64, reason: Option<String>) {
+        // The previous process, its ports and its capture files belong to a
+        // stop that may still be running.
+        self.wait_for_stop(&def.id);
+        if let Err((dep, err)) = self.wait_for_dependencies(&def, launch) {
+            self.finish_blocked(&def.id, launch, &dep, &err);
+            return;
+        }
+        let def = match self.render(&def) {
+            Ok(def) => def,
+            Err(err) => {
+                self.finish_launch(&def.id, launch, None, Err(err));
+                return;
+            }
+        };
+        let binary = match self.runtime.resolve_binary(&def.binary) {
+            Ok(path) => path,
+            Err(err) => {
+                if let Some(version) = runtime::default_versions().get(&def.id).cloned() {
+                    let _ = self.runtime.ensure_service(&def.id, &version);
+                }
+                match self.runtime.resolve_binary(&def.binary) {
+                    Ok(path) => path,
+                    Err(_) => {
+                        self.finish_launch(&def.id, launch, None, Err(err));
+                        return;
+                    }
+                }
+            }
+        };
+        self.ensure_service_data(&def, &binary);
+        let context = self.template_context(&def);
+        let config_dir = context.runtime_dir().join("config").join(&def.id);
+        match template::render_files(&config_dir, &context) {
+            Ok(written) => {
+                for path in written {
+                    self.push_log(&def.id, "info", &format!("rendered {}", path.display()));
+                }
+            }
+            Err(err) => {
+                self.finish_launch(&def.id, launch, None, Err(err));
+                return;
+            }
+        }
+        if let Err(err) = self.run_hooks(&def, "pre-start", &def.hooks.pre_start) {
+            self.finish_launch(&def.id, launch, None, Err(err));
+            return;
+        }
+        // Output of the previous run has to be read before its files are reused.
+        self.finish_capture(&def.id);
+        let log_root = self.log_files.root();
+        let output = log_capture::create(&log_capture::stdout_path(log_root, &def.id)).and_then(
+            |stdout| Ok((stdout, log_capture::create(&log_capture::stderr_path(log_root, &def.id))?)),
+        );
+        let (stdout, stderr) = match output {
+            Ok(output) => output,
+            Err(err) => {
+                self.finish_launch(&def.id, launch, None, Err(err));
+                return;
+            }
+        };
+        let mut cmd = Command::new(&binary);
+        cmd.args(&def.args)
+            .current_dir(&def.cwd)
+            .envs(&def.env)
+            .stdin(Stdio::null())
+            .stdout(stdout)
+            .stderr(stderr);
+        let path_value = self.runtime.scoped_path(&binary);
+        cmd.env("PATH", path_value);
+        process::isolate(&mut cmd);
+
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(err) => {
+                self.finish_launch(&def.id, launch, None, Err(err.to_string()));
+                return;
+            }
+        };
+        let pid = Some(child.id());
+        if let Err(err) = process::contain(&child) {
+            self.push_log(&def.id, "warn", &format!("failed to contain process: {err}"));
+        }
+        self.record_pid(&def, &binary, child.id());
+        self.capture_logs(&def.id, def.log_format.clone(), child.id(), false);
+        {
+            // Holding the launches lock while publishing the child means a
+            // concurrent stop either finds it or has already cancelled us.
+            let launches = self.launches.lock().expect("launches lock");
+            if launches.get(&def.id) != Some(&launch) {
+                drop(launches);
+                let _ = process::kill_tree(child.id());
+                let _ = child.kill();
+                let _ = child.wait();
+                self.forget_pid(&def.id);
+                self.finish_capture(&def.id);
+                return;
+            }
+            self.processes
+                .lock()
+                .expect("processes lock")
+                .insert(def.id.clone(), child);
+            self.effective
+                .lock()
+                .expect("effective lock")
+                .insert(def.id.clone(), def.clone());
+            self.set_state(ServiceState {
+                pid,
+                ..ServiceState::new(&def.id, "starting", now_ts())
+            });
+        }
+        self.open_run(&def.id, pid, reason);
+        self.push_log(&def.id, "info", "service started");
+
+        let health = self.check_health_with_retries(&def, launch);
+        if self.is_current(&def.id, launch) {
+            self.record_health(&def.id, &health);
+        }
+        if health.is_ok() && self.is_current(&def.id, launch) {
+            if let Err(err) = self.run_hooks(&def, "post-start", &def.hooks.post_start) {
+                self.discard_process(&def.id, &err);
+                self.finish_launch(&def.id, launch, None, Err(err));
+                return;
+            }
+        }
+        self.finish_launch(&def.id, launch, pid, health);
+    }
+
+    // Kills a process whose start failed after it was spawned.
+    fn discard_process(&self, id: &str, err: &str) {
+        let child = self.processes.lock().expect("processes lock").remove(id);
+        if let Some(mut child) = child {
+            let _ = process::kill_tree(child.id());
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        self.effective.lock().expect("effective lock").remove(id);
+        self.forget_pid(id);
+        self.finish_capture(id);
+        let stderr_tail = self.stderr_tail(id);
+        self.close_run(id, "failed", |run| {
+            run.error = Some(err.to_string());
+            run.stderr_tail = stderr_tail;
+        });
+    }
+
+    fn open_run(&self, id: &str, pid: Option<u32>, reason: Option<String>) {
+        let Some(runs) = &self.runs else {
+            return;
+        };
+        let record = RunRecord {
+            service: id.to_string(),
+            run: 0,
+            pid,
+            started_at: now_ts(),
+            stopped_at: None,
+            uptime_secs: None,
+            outcome: "running".to_string(),
+            exit_code: None,
+            signal: None,
+            restart_reason: reason,
+            stop_method: None,
+            error: None,
+            stderr_tail: Vec::new(),
+        };
+        if let Err(err) = runs.open(record) {
+            self.push_log(id, "warn", &format!("failed to record run: {err}"));
+        }
+    }
+
+    fn close_run(&self, id: &str, outcome: &str, update: impl FnOnce(&mut RunRecord)) {
+        let Some(runs) = &self.runs else {
+            return;
+        };
+        if let Err(err) = runs.close(id, outcome, &now_ts(), update) {
+            self.push_log(id, "warn", &format!("failed to record run: {err}"));
+        }
+    }
+
+    fn stderr_tail(&self, id: &str) -> Vec<String> {
+        self.stderr_tail
+            .lock()
+            .expect("stderr tail lock")
+            .get(id)
+            .map(|tail| tail.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn wait_for_dependencies(
+        &self,
+        def: &ServiceDefinition,
+        launch: u64,
+    ) -> Result<(), (String, String)> {
+        let deadline = Instant::now() + Duration::from_millis(def.dependency_timeout_ms);
+        for dep in &def.depends_on {
+            loop {
+                if !self.is_current(&def.id, launch) {
+                    return Err((dep.clone(), "start cancelled".to_string()));
+                }
+                let state = match self.state(dep) {
+                    Some(state) => state,
+                    None => return Err((dep.clone(), "not started".to_string())),
+                };
+                match state.state.as_str() {
+                    "running" => break,
+                    "starting" | "restarting" => {}
+                    other => {
+                        let reason = state.last_error.unwrap_or_else(|| other.to_string());
+                        return Err((dep.clone(), reason));
+                    }
+                }
+                if Instant::now() >= deadline {
+                    return Err((
+                        dep.clone(),
+                        format!("not healthy after {}ms", def.dependency_timeout_ms),
+                    ));
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+        }
+        Ok(())
+    }
+
+    fn finish_blocked(&self, id: &str, launch: u64, dep: &str, err: &str) {
+        let mut launches = self.launches.lock().expect("launches lock");
+        if launches.get(id) != Some(&launch) {
+            return;
+        }
+        launches.remove(id);
+        let message = format!("dependency {dep} failed: {err}");
+        self.push_log(id, "error", &message);
+        self.set_state(ServiceState {
+            last_error: Some(message),
+            blocked_by: Some(dep.to_string()),
+            ..ServiceState::new(id, "blocked", now_ts())
+        });
+    }
+
+    fn finish_launch(&self, id: &str, launch: u64, pid: Option<u32>, result: Result<(), String>) {
+        let mut launches = self.launches.lock().expect("launches lock");
+        if launches.get(id) != Some(&launch) {
+            return;
+        }
+        launches.remove(id);
+        let (state_value, last_error) = match result {
+            Ok(()) => ("running".to_string(), None),
+            Err(err) => {
+                self.push_log(id, "error", &format!("service failed to start: {err}"));
+                ("error".to_string(), Some(err))
+            }
+        };
+        self.set_state(ServiceState {
+            pid,
+            last_error,
+            ..ServiceState::new(id, &state_value, now_ts())
+        });
+    }
+
+    fn record_pid(&self, def: &ServiceDefinition, binary: &Path, pid: u32) {
+        let store = match &self.pids {
+            Some(store) => store,
+            None => return,
+        };
+        let record = PidRecord {
+            pid,
+            started_at: process::start_time(pid).unwrap_or_default(),
+            binary: binary.to_string_lossy().to_string(),
+            ports: def.ports.iter().map(|port| port.port).collect(),
+        };
+        if let Err(err) = store.save(&def.id, &record) {
+            self.push_log(&def.id, "warn", &format!("failed to write pidfile: {err}"));
+        }
+    }
+
+    fn forget_pid(&self, id: &str) {
+        if let Some(store) = &self.pids {
+            let _ = store.remove(id);
+        }
+    }
+
+    fn shutdown(
+        &self,
+        id: &str,
+        def: Option<&ServiceDefinition>,
+        target: Tracked,
+    ) -> Result<String, String> {
+        // Snapshot the tree before the root goes away and its children get
+        // reparented, then sweep whatever the root left behind.
+        let pid = target.pid();
+        let tree = process::descendants(pid);
+        let method = self.shutdown_root(id, def, target)?;
+        // Daemonized children left the tree but not the process group.
+        process::kill_group(pid);
+        let orphans: Vec<ProcessRef> = tree.into_iter().filter(process::is_same_process).collect();
+        if !orphans.is_empty() {
+            self.push_log(
+                id,
+                "warn",
+                &format!("killing {} orphaned child process(es)", orphans.len()),
+            );
+            let survivors = process::kill_processes(&orphans);
+            self.record_leaks(id, &survivors);
+        }
+        self.lineage.lock().expect("lineage lock").remove(id);
+        Ok(method)
+    }
+
+    fn record_leaks(&self, id: &str, orphans: &[ProcessRef]) {
+        if orphans.is_empty() {
+            return;
+        }
+        let mut leaked = self.leaked.lock().expect("leaked lock");
+        for orphan in orphans {
+            self.push_log(
+                id,
+                "warn",
+                &format!(
+                    "leaked process {} ({}) outlived the service",
+                    orphan.pid, orphan.name
+                ),
+            );
+            leaked.push(LeakedProcess {
+                service: id.to_string(),
+                pid: orphan.pid,
+                name: orphan.name.clone(),
+                started_at: orphan.started_at,
+            });
+        }
+    }
+
+    fn shutdown_root(
+        &self,
+        id: &str,
+        def: Option<&ServiceDefinition>,
+        mut target: Tracked,
+    ) -> Result<String, String> {
+        let policy = def.map(|d| d.stop_policy.clone()).unwrap_or_default();
+        let deadline = Instant::now() + Duration::from_millis(policy.timeout_ms);
+
+        if let (Some(def), false) = (def, policy.command.is_empty()) {
+            match self.run_command(def, &policy.command, deadline) {
+                Ok(()) => {
+                    if target.wait(remaining(deadline)) {
+                        return Ok("command".to_string());
+                    }
+                    self.push_log(id, "warn", "stop command did not stop the process in time");
+                }
+                Err(err) => self.push_log(id, "warn", &format!("stop command failed: {err}")),
+            }
+        }
+
+        // Only the root gets the stop signal, so a postmaster or fpm master
+        // can shut its workers down in order; the group is killed only once
+        // the timeout runs out.
+        if !policy.signal.is_empty() && !remaining(deadline).is_zero() {
+            match process::send_signal(target.pid(), &policy.signal) {
+                Ok(()) => {
+                    if target.wait(remaining(deadline)) {
+                        return Ok("signal".to_string());
+                    }
+                    self.push_log(
+                        id,
+                        "warn",
+                        "process ignored stop signal, escalating to kill",
+                    );
+                }
+                Err(err) => self.push_log(id, "warn", &format!("stop signal failed: {err}")),
+            }
+        }
+
+        target.kill()?;
+        Ok("kill".to_string())
+    }
+
+    // Tries the reload command, then the signal. Returns the method that worked.
+    fn reload(&self, def: &ServiceDefinition, pid: u32) -> Result<String, String> {
+        let policy = def
+            .reload_policy
+            .clone()
+            .ok_or_else(|| format!("{} has no reload policy", def.id))?;
+        let mut last_error = None;
+        if !policy.command.is_empty() {
+            let deadline = Instant::now() + Duration::from_millis(policy.timeout_ms);
+            match self.run_command(def, &policy.command, deadline) {
+                Ok(()) => return Ok("command".to_string()),
+                Err(err) => {
+                    self.push_log(&def.id, "warn", &format!("reload command failed: {err}"));
+                    last_error = Some(err);
+                }
+            }
+        }
+        if let Some(signal) = &policy.signal {
+            return process::send_signal(pid, signal).map(|_| "signal".to_string());
+        }
+        Err(last_error.unwrap_or_else(|| "reload policy is empty".to_string()))
+    }
+
+    fn run_command(
+        &self,
+        def: &ServiceDefinition,
+        command: &[String],
+        deadline: Instant,
+    ) -> Result<(), String> {
+        let (program, args) = command
+            .split_first()
+            .ok_or_else(|| "command is empty".to_string())?;
+        let mut cmd = self.scoped_command(def, program);
+        cmd.args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        let mut child = cmd.spawn().map_err(|e| e.to_string())?;
+        // Read stderr while the command runs; a full pipe would block it.
+        let stderr = child.stderr.take().map(|mut stream| {
+            thread::spawn(move || {
+                let mut raw = Vec::new();
+                let _ = stream.read_to_end(&mut raw);
+                raw
+            })
+        });
+        if !process::wait_for_exit(&mut child, remaining(deadline)) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err("command timed out".to_string());
+        }
+        let status = child.wait().map_err(|e| e.to_string())?;
+        if status.success() {
+            return Ok(());
+        }
+        let stderr = stderr
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default();
+        let stderr = String::from_utf8_lossy(&stderr).trim().to_string();
+        Err(if stderr.is_empty() {
+            format!("exited with {status}")
+        } else {
+            stderr
+        })
+    }
+
+    // A command that sees what the service sees: its cwd, env and PATH.
+    fn scoped_command(&self, def: &ServiceDefinition, program: &str) -> Command {
+        let mut cmd = Command::new(program);
+        cmd.current_dir(&def.cwd).envs(&def.env);
+        if let Ok(binary) = self.runtime.resolve_binary(&def.binary) {
+            cmd.env("PATH", self.runtime.scoped_path(&binary));
+        }
+        cmd
+    }
+
+    fn run_hooks(
+        &self,
+        def: &ServiceDefinition,
+        phase: &str,
+        hooks: &[Hook],
+    ) -> Result<(), String> {
+        for hook in hooks {
+            let Err(err) = self.run_hook(def, phase, hook) else {
+                continue;
+            };
+            let message = format!("{phase} hook failed: {err}");
+            if hook.on_failure == "warn" {
+                self.push_log(&def.id, "warn", &message);
+                continue;
+            }
+            self.push_log(&def.id, "error", &message);
+            return Err(message);
+        }
+        Ok(())
+    }
+
+    fn run_hook(&self, def: &ServiceDefinition, phase: &str, hook: &Hook) -> Result<(), String> {
+        let (program, args) = hook
+            .command
+            .split_first()
+            .ok_or_else(|| "hook command is empty".to_string())?;
+        let mut cmd = self.scoped_command(def, program);
+        cmd.args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        process::isolate(&mut cmd);
+        self.push_log(
+            &def.id,
+            "info",
+            &format!("running {phase} hook: {}", hook.command.join(" ")),
+        );
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| format!("failed to run {program}: {e}"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .map(|stream| self.spawn_hook_reader(&def.id, phase, "info", stream));
+        let stderr = child
+            .stderr
+            .take()
+            .map(|stream| self.spawn_hook_reader(&def.id, phase, "warn", stream));
+        let finished = process::wait_for_exit(&mut child, Duration::from_millis(hook.timeout_ms));
+        if !finished {
+            let _ = process::kill_tree(child.id());
+            let _ = child.kill();
+        }
+        let status = child.wait().map_err(|e| e.to_string())?;
+        let last_line = |reader: Option<JoinHandle<Option<String>>>| {
+            reader.and_then(|reader| reader.join().ok().flatten())
+        };
+        let (stderr, stdout) = (last_line(stderr), last_line(stdout));
+        if !finished {
+            return Err(format!("{program} timed out after {}ms", hook.timeout_ms));
+        }
+        if status.success() {
+            return Ok(());
+        }
+        let status = match status.code() {
+            Some(code) => format!("{program} exited with code {code}"),
+            None => format!("{program} was terminated"),
+        };
+        match stderr.or(stdout) {
+            Some(line) => Err(format!("{status}: {line}")),
+            None => Err(status),
+        }
+    }
+
+    // Copies hook output into the service log, tagged with the phase, and
+    // returns the last non-empty line for error messages.
+    fn spawn_hook_reader(
+        &self,
+        id: &str,
+        phase: &str,
+        level: &'static str,
+        stream: impl std::io::Read + Send + 'static,
+    ) -> JoinHandle<Option<String>> {
+        let logs = self.logs.clone();
+        let log_seq = self.log_seq.clone();
+        let events = self.events.clone();
+        let log_files = self.log_files.clone();
+        let log_limit = self.log_limit;
+        let id = id.to_string();
+        let phase = phase.to_string();
+        thread::spawn(move || {
+            let mut last = None;
+            for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                let line = line.trim_end().to_string();
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let entry = LogEntry {
+                    ts: String::new(),
+                    seq: 0,
+                    level: level.to_string(),
+                    service: id.clone(),
+                    message: line.clone(),
+                    fields: HashMap::from([("hook".to_string(), phase.clone())]),
+                };
+                push_log_shared(&logs, &log_seq, &events, &log_files, entry, log_limit);
+                last = Some(line.trim().to_string());
+            }
+            last
+        })
+    }
+
+    fn push_log(&self, id: &str, level: &str, message: &str) {
+        let entry = LogEntry {
+            ts: String::new(),
+            seq: 0,
+            level: level.to_string(),
+            service: id.to_string(),
+            message: message.to_string(),
+            fields: HashMap::new(),
+        };
+        push_log_shared(
+            &self.logs,
+            &self.log_seq,
+            &self.events,
+            &self.log_files,
+            entry,
+            self.log_limit,
+        );
+    }
+
+    // Follows the capture files of the process `pid`. An adopted process
+    // resumes where the previous session stopped reading its files.
+    fn capture_logs(&self, id: &str, log_format: Option<String>, pid: u32, resume: bool) {
+        if !resume {
+            self.stderr_tail
+                .lock()
+                .expect("stderr tail lock")
+                .remove(id);
+        }
+        let root = self.log_files.root();
+        let tail = |path: PathBuf| {
+            if resume {
+                Tail::resume(path)
             } else {
-                self.push_log(&def.id, "error", "mariadb-install-db not found");
+                Tail::new(path)
             }
+        };
+        let tails = vec![
+            ("info", tail(log_capture::stdout_path(root, id))),
+            ("error", tail(log_capture::stderr_path(root, id))),
+        ];
+        let logs = self.logs.clone();
+        let log_seq = self.log_seq.clone();
+        let events = self.events.clone();
+        let log_files = self.log_files.clone();
+        let stderr_tail = self.stderr_tail.clone();
+        let log_limit = self.log_limit;
+        let service = id.to_string();
+        let follower = Follower::spawn(
+            tails,
+            move || process::is_alive(pid),
+            move |stream_level, line| {
+                if stream_level == "error" {
+                    let mut tails = stderr_tail.lock().expect("stderr tail lock");
+                    let tail = tails.entry(service.clone()).or_default();
+                    tail.push_back(line.clone());
+                    if tail.len() > STDERR_TAIL_LINES {
+                        tail.pop_front();
+                    }
+                }
+                let parsed = log_parser::parse_line(log_format.as_deref(), stream_level, &line);
+                let entry = LogEntry {
+                    ts: String::new(),
+                    seq: 0,
+                    level: parsed.level,
+                    service: service.clone(),
+                    message: parsed.message,
+                    fields: parsed.fields,
+                };
+                push_log_shared(&logs, &log_seq, &events, &log_files, entry, log_limit);
+            },
+        );
+        let previous = self
+            .captures
+            .lock()
+            .expect("captures lock")
+            .insert(id.to_string(), follower);
+        if let Some(previous) = previous {
+            previous.finish();
         }
     }
 
+    // Waits for the last output of a service that is gone to be logged.
+    fn finish_capture(&self, id: &str) {
+        let follower = self.captures.lock().expect("captures lock").remove(id);
+        if let Some(follower) = follower {
+            follower.finish();
+        }
+    }
+
+    fn ensure_service_data(&self, def: &ServiceDefinition, binary: &Path) {
+        let Some(driver) = drivers::find(&def.id) else {
+            return;
+        };
+        let context = self.driver_context(def, binary);
+        if let Err(err) = driver.init_data(&context) {
+            self.push_log(&def.id, "error", &format!("data init failed: {err}"));
+        }
+    }
+
+    fn driver_context(&self, def: &ServiceDefinition, binary: &Path) -> DriverContext {
+        let data_dir = self.template_context(def).data_dir();
+        DriverContext::new(def.clone(), binary.to_path_buf(), data_dir)
+            .with_path(self.runtime.scoped_path(binary))
+    }
+
+    fn template_context(&self, def: &ServiceDefinition) -> TemplateContext {
+        TemplateContext::for_service(&self.runtime.root, def).with_secrets(self.secrets.clone())
+    }
+
+    fn render(&self, def: &ServiceDefinition) -> Result<ServiceDefinition, String> {
+        self.template_context(def).render_definition(def)
+    }
+
     fn check_health(&self, def: &ServiceDefinition) -> Result<(), String> {
         let kind = def.health_check.kind.as_str();
         match kind {
             "pid" => Ok(()),
-            "port" | "http" => {
+            "http" => {
+                let probe = def.health_check.http.clone().unwrap_or_default();
+                health::probe_http(
+                    &def.health_check.target,
+                    def.health_check.timeout_ms,
+                    &probe,
+                )
+            }
+            "port" => {
                 let addr = resolve_addr(&def.health_check.target)?;
                 let timeout = Duration::from_millis(def.health_check.timeout_ms);
                 TcpStream::connect_timeout(&addr, timeout)
                     .map(|_| ())
                     .map_err(|e| e.to_string())
             }
+            "exec" => {
+                let (program, args) = def
+                    .health_check
+                    .command
+                    .split_first()
+                    .ok_or_else(|| "health check command is empty".to_string())?;
+                let mut cmd = self.scoped_command(def, program);
+                cmd.args(args);
+                health::probe_exec(cmd, def.health_check.timeout_ms)
+            }
             _ => Err("unsupported health check".to_string()),
         }
     }
 
-    fn check_health_with_retries(&self, def: &ServiceDefinition) -> Result<(), String> {
+    fn check_health_with_retries(
+        &self,
+        def: &ServiceDefinition,
+        launch: u64,
+    ) -> Result<(), String> {
         let mut last_error = None;
         for _ in 0..self.health_retries {
+            if !self.is_current(&def.id, launch) {
+                return Err("start cancelled".to_string());
+            }
             match self.check_health(def) {
                 Ok(()) => return Ok(()),
                 Err(err) => last_error = Some(err),
@@ -507,117 +2210,130 @@ impl ServiceManager {
         }
         Err(last_error.unwrap_or_else(|| "health check failed".to_string()))
     }
+}
 
-    pub fn health(&self, id: &str) -> Result<String, String> {
-        let def = self
-            .definitions
-            .iter()
-            .find(|d| d.id == id)
-            .ok_or_else(|| format!("service not found: {id}"))?;
-        self.check_health(def).map(|_| "ok".to_string())
+// Services outlive the manager; their output is no longer read but stays
+// in the capture files, where the next session resumes.
+impl Drop for ServiceManager {
+    fn drop(&mut self) {
+        let captures: Vec<Follower> = self
+            .shared
+            .captures
+            .lock()
+            .expect("captures lock")
+            .drain()
+            .map(|(_, follower)| follower)
+            .collect();
+        for follower in captures {
+            follower.finish();
+        }
     }
+}
 
-    fn refresh_health(&mut self) {
-        let ids: Vec<String> = self.states.keys().cloned().collect();
-        for id in ids {
-            let state = match self.states.get(&id) {
-                Some(state) => state.clone(),
-                None => continue,
-            };
-            if state.state != "running" && state.state != "starting" {
-                continue;
-            }
-            let def = match self.definitions.iter().find(|d| d.id == id) {
-                Some(def) => def,
-                None => continue,
-            };
-            match self.check_health(def) {
-                Ok(()) => {
-                    if state.state != "running" {
-                        self.states.insert(
-                            id.clone(),
-                            ServiceState {
-                                id: id.clone(),
-                                state: "running".to_string(),
-                                pid: state.pid,
-                                last_error: None,
-                                last_updated: now_ts(),
-                            },
-                        );
-                    }
-                }
-                Err(err) => {
-                    self.states.insert(
-                        id.clone(),
-                        ServiceState {
-                            id: id.clone(),
-                            state: "error".to_string(),
-                            pid: state.pid,
-                            last_error: Some(err),
-                            last_updated: now_ts(),
-                        },
-                    );
-                }
-            }
+// A health check resolved under the manager lock, to run without it.
+#[derive(Debug)]
+pub struct HealthTask {
+    shared: Shared,
+    def: ServiceDefinition,
+}
+
+impl HealthTask {
+    pub fn run(&self) -> Result<String, String> {
+        self.shared.check_health(&self.def).map(|_| "ok".to_string())
+    }
+}
+
+// The periodic probes of one tick, to run without the manager lock.
+#[derive(Debug)]
+pub struct HealthRound {
+    shared: Shared,
+    probes: Vec<(ServiceState, ServiceDefinition)>,
+}
+
+impl HealthRound {
+    pub fn run(self) {
+        for (state, def) in self.probes {
+            let started = Instant::now();
+            let result = self.shared.check_health(&def);
+            self.shared.apply_probe(&state, started, result);
         }
     }
+}
 
-    fn poll_process_exits(&mut self) {
-        let ids: Vec<String> = self.processes.keys().cloned().collect();
-        for id in ids {
-            let exit = if let Some(child) = self.processes.get_mut(&id) {
-                match child.try_wait() {
-                    Ok(status) => status,
-                    Err(_) => None,
-                }
-            } else {
-                None
-            };
+// Set by a stop worker once the service is down.
+#[derive(Debug, Default)]
+struct StopDone {
+    done: Mutex<bool>,
+    finished: Condvar,
+}
 
-            if let Some(status) = exit {
-                self.processes.remove(&id);
-                let success = status.success();
-                let (state, last_error) = if success {
-                    ("stopped".to_string(), None)
-                } else {
-                    ("error".to_string(), Some("process exited".to_string()))
-                };
-                self.states.insert(
-                    id.clone(),
-                    ServiceState {
-                        id: id.clone(),
-                        state,
-                        pid: None,
-                        last_error,
-                        last_updated: now_ts(),
-                    },
-                );
-                let level = if success { "info" } else { "error" };
-                self.push_log(&id, level, "process exited");
-                if !success {
-                    if let Some(def) = self.definitions.iter().find(|d| d.id == id) {
-                        let attempt = self.restart_attempts.entry(id.clone()).or_insert(0);
-                        if *attempt < def.restart_policy.max_retries {
-                            *attempt += 1;
-                            self.push_log(&id, "info", "restarting after crash");
-                            std::thread::sleep(Duration::from_millis(def.restart_policy.backoff_ms));
-                            let _ = self.start(&id);
-                        }
-                    }
-                } else {
-                    self.restart_attempts.insert(id.clone(), 0);
+impl StopDone {
+    fn finish(&self) {
+        *self.done.lock().expect("stop done lock") = true;
+        self.finished.notify_all();
+    }
+
+    fn wait(&self) {
+        let mut done = self.done.lock().expect("stop done lock");
+        while !*done {
+            done = self.finished.wait(done).expect("stop done lock");
+        }
+    }
+}
+
+// A service process we can stop: either our own child or one adopted from a
+// previous session.
+enum Tracked<'a> {
+    Child(&'a mut Child),
+    Adopted(u32),
+}
+
+impl Tracked<'_> {
+    fn pid(&self) -> u32 {
+        match self {
+            Tracked::Child(child) => child.id(),
+            Tracked::Adopted(pid) => *pid,
+        }
+    }
+
+    fn wait(&mut self, timeout: Duration) -> bool {
+        match self {
+            Tracked::Child(child) => process::wait_for_exit(child, timeout),
+            Tracked::Adopted(pid) => process::wait_for_pid_exit(*pid, timeout),
+        }
+    }
+
+    fn kill(&mut self) -> Result<(), String> {
+        match self {
+            Tracked::Child(child) => {
+                if process::kill_tree(child.id()).is_err() {
+                    child.kill().map_err(|e| e.to_string())?;
                 }
+                let _ = child.wait();
+                Ok(())
+            }
+            Tracked::Adopted(pid) => {
+                process::kill_tree(*pid)?;
+                process::wait_for_pid_exit(*pid, Duration::from_secs(5));
+                Ok(())
             }
         }
     }
 }
 
 fn now_ts() -> String {
-    let secs = SystemTime::now()
+    now_secs().to_string()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
-        .as_secs();
-    secs.to_string()
+        .as_secs()
+}
+
+fn remaining(deadline: Instant) -> Duration {
+    deadline.saturating_duration_since(Instant::now())
 }
 
 fn resolve_addr(target: &str) -> Result<SocketAddr, String> {
@@ -640,61 +2356,34 @@ fn resolve_host_port(target: &str) -> Result<SocketAddr, String> {
         .ok_or_else(|| "unable to resolve target".to_string())
 }
 
+// Stamps `ts` and `seq` under the logs lock so stdout and stderr lines of a
+// service get a consistent order.
 fn push_log_shared(
     logs: &Arc<Mutex<HashMap<String, Vec<LogEntry>>>>,
-    log_root: &PathBuf,
-    id: &str,
-    level: &str,
-    message: &str,
+    log_seq: &Arc<Mutex<HashMap<String, u64>>>,
+    events: &EventBus,
+    log_files: &LogFiles,
+    mut entry: LogEntry,
     log_limit: usize,
 ) {
-    let entry = LogEntry {
-        ts: now_ts(),
-        level: level.to_string(),
-        service: id.to_string(),
-        message: message.to_string(),
-        fields: HashMap::new(),
-    };
-    let mut logs = logs.lock().expect("logs lock");
-    let buffer = logs.entry(id.to_string()).or_default();
-    buffer.push(entry);
-    if buffer.len() > log_limit {
-        let extra = buffer.len() - log_limit;
-        buffer.drain(0..extra);
-    }
-    let _ = write_log_line(log_root, id, level, message);
-}
-
-fn write_log_line(root: &PathBuf, id: &str, level: &str, message: &str) -> Result<(), String> {
-    let dir = root;
-    let _ = std::fs::create_dir_all(dir);
-    let path = dir.join(format!("{id}.log"));
-    rotate_log_if_needed(&path, 10 * 1024 * 1024, 3)?;
-    let mut file = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&path)
-        .map_err(|e| e.to_string())?;
-    use std::io::Write;
-    writeln!(file, "{} [{}] {}", now_ts(), level, message).map_err(|e| e.to_string())
-}
-
-fn rotate_log_if_needed(path: &PathBuf, max_size: u64, max_files: u32) -> Result<(), String> {
-    if let Ok(metadata) = std::fs::metadata(path) {
-        if metadata.len() < max_size {
-            return Ok(());
-        }
-        for index in (1..=max_files).rev() {
-            let src = if index == 1 {
-                path.clone()
-            } else {
-                PathBuf::from(format!("{}.{}", path.display(), index - 1))
-            };
-            let dst = PathBuf::from(format!("{}.{}", path.display(), index));
-            if src.exists() {
-                let _ = std::fs::rename(&src, &dst);
-            }
+    {
+        let mut logs = logs.lock().expect("logs lock");
+        let mut log_seq = log_seq.lock().expect("log seq lock");
+        let seq = log_seq.entry(entry.service.clone()).or_insert(0);
+        *seq += 1;
+        entry.seq = *seq;
+        // Stamped with the time the line was read: services log local times
+        // or epoch milliseconds that cannot be ordered reliably, so their own
+        // time stays in fields["time"].
+        entry.ts = log_query::format_millis(log_query::now_millis());
+        let buffer = logs.entry(entry.service.clone()).or_default();
+        buffer.push(entry.clone());
+        if buffer.len() > log_limit {
+            let extra = buffer.len() - log_limit;
+            buffer.drain(0..extra);
         }
     }
-    Ok(())
+    let line = format!("{} [{}] {}", entry.ts, entry.level, entry.message);
+    let _ = log_files.append(&entry.service, &line);
+    events.emit(ServiceEvent::LogLine { entry });
 }
@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
 use std::collections::HashMap;
+use crate::process;
 use crate::runtime::RuntimeManager;
 
 #[derive(Debug)]
@@ -64,6 +65,8 @@ impl TaskManager {
            .env("PATH", path_env)
            .stdout(Stdio::piped())
            .stderr(Stdio::piped());
+        // npm spawns node/vite grandchildren; keep them in one group so stop reaches them.
+        process::isolate(&mut cmd);
 
         let child = cmd.spawn().map_err(|e| e.to_string())?;
         self.processes.insert(project_id.to_string(), child);
@@ -72,7 +75,10 @@ impl TaskManager {
 
     pub fn stop_task(&mut self, project_id: &str) -> Result<(), String> {
         if let Some(mut child) = self.processes.remove(project_id) {
-            child.kill().map_err(|e| e.to_string())?;
+            if process::kill_tree(child.id()).is_err() {
+                child.kill().map_err(|e| e.to_string())?;
+            }
+            let _ = child.wait();
             return Ok(());
         }
         Err("No task running".to_string())
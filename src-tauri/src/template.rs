@@ -0,0 +1,249 @@
+use crate::models::{PortDef, ServiceDefinition};
+use crate::secrets::SecretsStore;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// Values for the ${...} placeholders in service definitions, ServiceConfig
+// overrides and config templates. Everything is resolved when the service is
+// spawned, so paths are absolute regardless of the app's working directory.
+//
+// ${root}         app root
+// ${runtime}      {root}/runtime
+// ${data_dir}     {root}/runtime/data/{service}
+// ${log_dir}      {root}/runtime/logs/{service}
+// ${port.<name>}  resolved port from ServiceConfig, profile or registry
+// ${version}      the service's version
+// ${secret:<key>} value from the secrets store
+//
+// "$${" is a literal "${", e.g. for shell variables in args.
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+    pub root: PathBuf,
+    pub service: String,
+    pub version: Option<String>,
+    pub ports: Vec<PortDef>,
+    pub secrets: Option<Arc<Mutex<SecretsStore>>>,
+    // Resolve ${secret:<key>} to a mask, for plans shown outside the process.
+    pub redact_secrets: bool,
+}
+
+pub const REDACTED: &str = "********";
+
+impl TemplateContext {
+    pub fn for_service(root: &Path, def: &ServiceDefinition) -> Self {
+        let root = std::path::absolute(root).unwrap_or_else(|_| root.to_path_buf());
+        Self {
+            root,
+            service: def.id.clone(),
+            version: def.version.clone(),
+            ports: def.ports.clone(),
+            secrets: None,
+            redact_secrets: false,
+        }
+    }
+
+    pub fn with_secrets(mut self, secrets: Option<Arc<Mutex<SecretsStore>>>) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    pub fn redacting_secrets(mut self) -> Self {
+        self.redact_secrets = true;
+        self
+    }
+
+    pub fn runtime_dir(&self) -> PathBuf {
+        self.root.join("runtime")
+    }
+
+    pub fn data_dir(&self) -> PathBuf {
+        self.runtime_dir().join("data").join(&self.service)
+    }
+
+    pub fn log_dir(&self) -> PathBuf {
+        self.runtime_dir().join("logs").join(&self.service)
+    }
+
+    pub fn render(&self, value: &str) -> Result<String, String> {
+        substitute(value, |name| self.lookup(name))
+    }
+
+    fn lookup(&self, name: &str) -> Result<String, String> {
+        let path = |path: PathBuf| path.to_string_lossy().to_string();
+        if let Some(port) = name.strip_prefix("port.") {
+            return self
+                .ports
+                .iter()
+                .find(|candidate| candidate.name == port)
+                .map(|candidate| candidate.port.to_string())
+                .ok_or_else(|| format!("unknown port: {port}"));
+        }
+        if let Some(key) = name.strip_prefix("secret:") {
+            let secrets = self
+                .secrets
+                .as_ref()
+                .ok_or_else(|| format!("secret not available: {key}"))?;
+            let value = secrets
+                .lock()
+                .expect("secrets lock")
+                .get(key)
+                .ok_or_else(|| format!("secret not found: {key}"))?;
+            return Ok(if self.redact_secrets {
+                REDACTED.to_string()
+            } else {
+                value
+            });
+        }
+        match name {
+            "root" => Ok(path(self.root.clone())),
+            "runtime" => Ok(path(self.runtime_dir())),
+            "data_dir" => Ok(path(self.data_dir())),
+            "log_dir" => Ok(path(self.log_dir())),
+            "version" => self
+                .version
+                .clone()
+                .ok_or_else(|| format!("no version for {}", self.service)),
+            _ => Err(format!("unknown variable: ${{{name}}}")),
+        }
+    }
+
+    pub fn render_definition(&self, def: &ServiceDefinition) -> Result<ServiceDefinition, String> {
+        map_definition(def, |value| self.render(value))
+    }
+}
+
+// Load-time check for custom definitions: every placeholder must be known,
+// without resolving secrets or paths.
+pub fn validate_definition(def: &ServiceDefinition) -> Result<(), String> {
+    map_definition(def, |value| {
+        substitute(value, |name| match name.split_once(['.', ':']) {
+            Some(("port", port)) if def.ports.iter().any(|p| p.name == port) => Ok(String::new()),
+            Some(("port", port)) => Err(format!("unknown port: {port}")),
+            Some(("secret", key)) if !key.is_empty() => Ok(String::new()),
+            None if matches!(
+                name,
+                "root" | "runtime" | "data_dir" | "log_dir" | "version"
+            ) =>
+            {
+                Ok(String::new())
+            }
+            _ => Err(format!("unknown variable: ${{{name}}}")),
+        })
+    })
+    .map(|_| ())
+}
+
+// Everything that reaches the spawned process or its probes.
+fn map_definition(
+    def: &ServiceDefinition,
+    render: impl Fn(&str) -> Result<String, String>,
+) -> Result<ServiceDefinition, String> {
+    let render_all = |values: &[String]| -> Result<Vec<String>, String> {
+        values.iter().map(|value| render(value)).collect()
+    };
+    let mut rendered = def.clone();
+    rendered.binary = render(&def.binary)?;
+    rendered.args = render_all(&def.args)?;
+    rendered.cwd = render(&def.cwd)?;
+    for (key, value) in rendered.env.iter_mut() {
+        *value = render(value).map_err(|e| format!("env {key}: {e}"))?;
+    }
+    rendered.health_check.target = render(&def.health_check.target)?;
+    rendered.health_check.command = render_all(&def.health_check.command)?;
+    rendered.stop_policy.command = render_all(&def.stop_policy.command)?;
+    if let Some(policy) = rendered.reload_policy.as_mut() {
+        policy.command = render_all(&policy.command)?;
+    }
+    for hook in rendered
+        .hooks
+        .pre_start
+        .iter_mut()
+        .chain(rendered.hooks.post_start.iter_mut())
+        .chain(rendered.hooks.pre_stop.iter_mut())
+        .chain(rendered.hooks.post_stop.iter_mut())
+    {
+        hook.command = render_all(&hook.command)?;
+    }
+    Ok(rendered)
+}
+
+fn substitute(
+    value: &str,
+    mut lookup: impl FnMut(&str) -> Result<String, String>,
+) -> Result<String, String> {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        if let Some(escaped) = after.strip_prefix("${") {
+            out.push_str("${");
+            rest = escaped;
+            continue;
+        }
+        let Some(inner) = after.strip_prefix('{') else {
+            out.push('$');
+            rest = after;
+            continue;
+        };
+        let end = inner
+            .find('}')
+            .ok_or_else(|| format!("unterminated placeholder in {value}"))?;
+        out.push_str(&lookup(&inner[..end])?);
+        rest = &inner[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+// Every {file}.tmpl under `dir` is rendered to {file}. Only files whose
+// content changed are written; their paths are returned.
+pub fn render_files(dir: &Path, context: &TemplateContext) -> Result<Vec<PathBuf>, String> {
+    let mut written = Vec::new();
+    for path in template_files(dir) {
+        let template = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let content = context
+            .render(&template)
+            .map_err(|e| format!("{name}: {e}"))?;
+        let target = path.with_extension("");
+        if fs::read_to_string(&target).ok().as_deref() == Some(content.as_str()) {
+            continue;
+        }
+        fs::write(&target, content).map_err(|e| e.to_string())?;
+        written.push(target);
+    }
+    written.sort();
+    Ok(written)
+}
+
+// The files render_files would produce under `dir`, changed or not.
+pub fn template_targets(dir: &Path) -> Vec<PathBuf> {
+    let mut targets: Vec<PathBuf> = template_files(dir)
+        .iter()
+        .map(|path| path.with_extension(""))
+        .collect();
+    targets.sort();
+    targets
+}
+
+fn template_files(dir: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return found;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            found.extend(template_files(&path));
+        } else if path.extension().and_then(|ext| ext.to_str()) == Some("tmpl") {
+            found.push(path);
+        }
+    }
+    found
+}
@@ -0,0 +1,61 @@
+// Shared helpers for the integration tests; not every test uses all of them.
+#![allow(dead_code)]
+
+use kojibox_lib::models::{
+    HealthCheck, LifecycleHooks, RestartPolicy, ServiceDefinition, ServiceState, StopPolicy,
+};
+use kojibox_lib::service_manager::ServiceManager;
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::time::Duration;
+
+pub fn free_port() -> u16 {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).expect("bind failed");
+    listener.local_addr().expect("local addr").port()
+}
+
+// Stops run on a worker; this waits until the service has settled.
+pub fn stop_and_wait(manager: &mut ServiceManager, id: &str) -> ServiceState {
+    manager.stop(id).expect("stop");
+    manager
+        .wait_for(id, Duration::from_secs(30))
+        .expect("state after stop")
+}
+
+// Minimal definitions for tests; set what matters with struct update syntax,
+// e.g. `ServiceDefinition { ports, ..ServiceDefinition::test("api") }`.
+pub trait TestDefinition {
+    fn test(id: &str) -> Self;
+    // Runs `script` with /bin/sh -c.
+    fn shell(id: &str, script: &str) -> Self;
+}
+
+impl TestDefinition for ServiceDefinition {
+    fn test(id: &str) -> Self {
+        ServiceDefinition {
+            id: id.to_string(),
+            name: id.to_string(),
+            binary: "/bin/sh".to_string(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            cwd: ".".to_string(),
+            ports: Vec::new(),
+            depends_on: Vec::new(),
+            dependency_timeout_ms: 60_000,
+            health_check: HealthCheck::default(),
+            restart_policy: RestartPolicy::default(),
+            stop_policy: StopPolicy::default(),
+            reload_policy: None,
+            hooks: LifecycleHooks::default(),
+            log_format: None,
+            version: None,
+        }
+    }
+
+    fn shell(id: &str, script: &str) -> Self {
+        ServiceDefinition {
+            args: vec!["-c".to_string(), script.to_string()],
+            ..ServiceDefinition::test(id)
+        }
+    }
+}
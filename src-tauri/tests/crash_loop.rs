@@ -0,0 +1,89 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{stop_and_wait, TestDefinition};
+use kojibox_lib::models::{HealthCheck, RestartPolicy, ServiceDefinition};
+use kojibox_lib::runtime::RuntimeManager;
+use kojibox_lib::service_manager::ServiceManager;
+use std::time::{Duration, Instant};
+
+fn crashing_service(max_retries: u32) -> ServiceDefinition {
+    ServiceDefinition {
+        health_check: HealthCheck {
+            interval_ms: 50,
+            ..HealthCheck::default()
+        },
+        restart_policy: RestartPolicy {
+            max_retries,
+            backoff_ms: 100,
+            max_backoff_ms: 400,
+            window_ms: 60_000,
+        },
+        ..ServiceDefinition::shell("crashy", "echo boom >&2; sleep 0.3; exit 3")
+    }
+}
+
+#[test]
+fn repeated_crashes_back_off_then_open_the_crash_loop_circuit() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new("."),
+        vec![crashing_service(2)],
+        temp_dir.path().join("logs"),
+    );
+    manager.start("crashy").expect("start");
+
+    let deadline = Instant::now() + Duration::from_secs(15);
+    let mut saw_restarting = false;
+    let state = loop {
+        let started = Instant::now();
+        manager.tick();
+        // Backoff sleeps on its own thread, never inside tick.
+        assert!(started.elapsed() < Duration::from_millis(100));
+        let state = manager.state("crashy").expect("state");
+        saw_restarting |= state.state == "restarting";
+        if state.state == "crash-loop" || Instant::now() > deadline {
+            break state;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+
+    assert!(saw_restarting);
+    assert_eq!(state.state, "crash-loop");
+    assert_eq!(state.last_exit_code, Some(3));
+    assert!(state.stderr_tail.iter().any(|line| line.contains("boom")));
+    assert!(state
+        .last_error
+        .as_deref()
+        .unwrap_or_default()
+        .starts_with("crash loop: 3 crashes"));
+
+    // A manual start clears the crash history and tries again.
+    manager.start("crashy").expect("restart by hand");
+    let state = manager.state("crashy").expect("state");
+    assert_ne!(state.state, "crash-loop");
+    stop_and_wait(&mut manager, "crashy");
+}
+
+#[test]
+fn zero_retries_leaves_the_service_in_error() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new("."),
+        vec![crashing_service(0)],
+        temp_dir.path().join("logs"),
+    );
+    manager.start("crashy").expect("start");
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let state = loop {
+        manager.tick();
+        let state = manager.state("crashy").expect("state");
+        if state.state == "error" || Instant::now() > deadline {
+            break state;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    assert_eq!(state.state, "error");
+    assert_eq!(state.last_exit_code, Some(3));
+}
@@ -0,0 +1,209 @@
+mod common;
+
+use common::{free_port, stop_and_wait};
+use kojibox_lib::config::load_services;
+use kojibox_lib::runtime::RuntimeManager;
+use kojibox_lib::service_manager::ServiceManager;
+use std::fs;
+use std::time::Duration;
+
+fn write_definition(root: &std::path::Path, file: &str, value: serde_json::Value) {
+    let dir = root.join("app/config/services.d");
+    fs::create_dir_all(&dir).expect("services.d");
+    fs::write(dir.join(file), value.to_string()).expect("write definition");
+}
+
+#[test]
+fn custom_definitions_are_validated_and_merged_with_builtins() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let root = temp_dir.path();
+    write_definition(
+        root,
+        "redis.json",
+        serde_json::json!({
+            "schemaVersion": 1,
+            "id": "redis",
+            "name": "Redis",
+            "binary": "redis-server",
+            "args": ["--port", "6379"],
+            "ports": [{"name": "main", "port": 6379, "protocol": "tcp"}],
+            "healthCheck": {"type": "port", "target": "127.0.0.1:6379", "timeoutMs": 1000, "intervalMs": 500}
+        }),
+    );
+    write_definition(
+        root,
+        "worker.json",
+        serde_json::json!({
+            "schemaVersion": 1,
+            "id": "worker",
+            "name": "Queue worker",
+            "binary": "php",
+            "dependsOn": ["redis", "mariadb"]
+        }),
+    );
+    write_definition(
+        root,
+        "bad-env.json",
+        serde_json::json!({
+            "schemaVersion": 1, "id": "bad", "name": "Bad", "binary": "bad",
+            "env": {"lower": "x"}
+        }),
+    );
+    write_definition(
+        root,
+        "dup.json",
+        serde_json::json!({"schemaVersion": 1, "id": "postgres", "name": "PG", "binary": "pg"}),
+    );
+    write_definition(
+        root,
+        "typo.json",
+        serde_json::json!({
+            "schemaVersion": 1, "id": "typo", "name": "Typo", "binary": "x",
+            "args": ["--data=${datadir}"]
+        }),
+    );
+    write_definition(
+        root,
+        "orphan.json",
+        serde_json::json!({
+            "schemaVersion": 1, "id": "orphan", "name": "Orphan", "binary": "x",
+            "dependsOn": ["missing"]
+        }),
+    );
+
+    let (definitions, errors) = load_services(root);
+    let ids: Vec<&str> = definitions.iter().map(|def| def.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        vec!["php", "node", "postgres", "mariadb", "mailpit", "redis", "worker"]
+    );
+    assert_eq!(errors.len(), 4, "{errors:?}");
+    assert!(errors
+        .iter()
+        .any(|err| err.contains("unknown variable: ${datadir}")));
+    assert!(errors.iter().any(|err| err.contains("invalid env key")));
+    assert!(errors
+        .iter()
+        .any(|err| err.contains("duplicate service id postgres")));
+    assert!(errors
+        .iter()
+        .any(|err| err.contains("unknown dependency missing")));
+
+    let worker = definitions.iter().find(|def| def.id == "worker").unwrap();
+    assert_eq!(worker.cwd, ".");
+    assert_eq!(worker.health_check.kind, "pid");
+    assert_eq!(worker.restart_policy.max_retries, 3);
+}
+
+#[test]
+fn custom_service_runs_like_a_builtin() {
+    let bin = std::env::var("CARGO_BIN_EXE_dummy_service").expect("dummy service bin");
+    let port = free_port();
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let root = temp_dir.path();
+    write_definition(
+        root,
+        "api.json",
+        serde_json::json!({
+            "schemaVersion": 1,
+            "id": "go-api",
+            "name": "Go API",
+            "binary": bin,
+            "args": [port.to_string()],
+            "ports": [{"name": "main", "port": port, "protocol": "tcp"}],
+            "healthCheck": {"type": "port", "target": format!("127.0.0.1:{port}"), "timeoutMs": 1000, "intervalMs": 200}
+        }),
+    );
+
+    let (definitions, errors) = load_services(root);
+    assert!(errors.is_empty(), "{errors:?}");
+    let mut manager =
+        ServiceManager::new(RuntimeManager::new(root), definitions, root.join("logs"));
+    assert!(manager.list().iter().any(|state| state.id == "go-api"));
+
+    manager.start("go-api").expect("start custom service");
+    let state = manager
+        .wait_for("go-api", Duration::from_secs(10))
+        .expect("state");
+    assert_eq!(state.state, "running");
+    assert_eq!(manager.health("go-api").expect("health"), "ok");
+    stop_and_wait(&mut manager, "go-api");
+}
+
+#[test]
+fn placeholders_render_into_definitions_and_templates() {
+    use kojibox_lib::config::default_services;
+    use kojibox_lib::secrets::SecretsStore;
+    use kojibox_lib::template::{render_files, TemplateContext};
+    use std::sync::{Arc, Mutex};
+
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let root = temp_dir.path();
+    let mut postgres = default_services()
+        .into_iter()
+        .find(|def| def.id == "postgres")
+        .unwrap();
+    postgres.ports[0].port = 5401;
+    let context = TemplateContext::for_service(root, &postgres);
+    let rendered = context.render_definition(&postgres).expect("render");
+    let data_dir = root.join("runtime/data/postgres").to_string_lossy().to_string();
+    let config_file = format!(
+        "config_file={}",
+        root.join("runtime/config/postgres/postgresql.conf").display()
+    );
+    assert_eq!(
+        rendered.args,
+        vec!["-D", data_dir.as_str(), "-p", "5401", "-c", config_file.as_str()]
+    );
+    assert_eq!(rendered.env["PGDATA"], data_dir);
+    assert_eq!(rendered.cwd, root.join("runtime").to_string_lossy());
+    assert!(rendered.health_check.command.contains(&"5401".to_string()));
+
+    let mut php = default_services()
+        .into_iter()
+        .find(|def| def.id == "php")
+        .unwrap();
+    php.ports[0].port = 9001;
+    let context = TemplateContext::for_service(root, &php);
+    let rendered = context.render_definition(&php).expect("render");
+    assert!(rendered.args.contains(&"0.0.0.0:9001".to_string()));
+    assert_eq!(rendered.health_check.target, "127.0.0.1:9001");
+
+    assert_eq!(context.render("$${HOME} $5").unwrap(), "${HOME} $5");
+    assert_eq!(
+        context.render("${port.admin}").unwrap_err(),
+        "unknown port: admin"
+    );
+    assert_eq!(
+        context.render("${home}").unwrap_err(),
+        "unknown variable: ${home}"
+    );
+    assert!(context.render("${secret:db_password}").is_err());
+    let mut secrets = SecretsStore::new(root).expect("secrets store");
+    secrets.set("db_password", "hunter2").expect("secret");
+    let context = context.with_secrets(Some(Arc::new(Mutex::new(secrets))));
+    assert_eq!(
+        context.render("--password=${secret:db_password}").unwrap(),
+        "--password=hunter2"
+    );
+
+    let dir = root.join("runtime/config/postgres");
+    fs::create_dir_all(&dir).expect("config dir");
+    fs::write(
+        dir.join("postgresql.conf.tmpl"),
+        "port=${port.main}\nlog_directory='${log_dir}'\n",
+    )
+    .expect("template");
+    let context = TemplateContext::for_service(root, &postgres);
+    let written = render_files(&dir, &context).expect("render templates");
+    assert_eq!(written, vec![dir.join("postgresql.conf")]);
+    let log_dir = root.join("runtime/logs/postgres");
+    assert_eq!(
+        fs::read_to_string(dir.join("postgresql.conf")).unwrap(),
+        format!("port=5401\nlog_directory='{}'\n", log_dir.display())
+    );
+    // Unchanged output is not rewritten.
+    assert!(render_files(&dir, &context)
+        .expect("render again")
+        .is_empty());
+}
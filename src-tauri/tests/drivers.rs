@@ -0,0 +1,166 @@
+#![cfg(unix)]
+
+use kojibox_lib::config::default_services;
+use kojibox_lib::drivers::{self, DriverContext};
+use kojibox_lib::template::TemplateContext;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+fn write_tool(dir: &Path, name: &str, script: &str) {
+    let path = dir.join(name);
+    fs::write(&path, format!("#!/bin/sh\n{script}\n")).expect("write tool");
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).expect("chmod");
+}
+
+fn postgres_context(root: &Path, port: u16) -> DriverContext {
+    let driver = drivers::find("postgres").expect("postgres driver");
+    let mut def = drivers::definition(driver.as_ref());
+    def.ports[0].port = port;
+    let template = TemplateContext::for_service(root, &def);
+    let def = template.render_definition(&def).expect("render");
+    let bin_dir = root.join("bin");
+    fs::create_dir_all(&bin_dir).expect("bin dir");
+    fs::create_dir_all(&def.cwd).expect("service cwd");
+    DriverContext::new(def, bin_dir.join("postgres"), template.data_dir())
+}
+
+#[test]
+fn built_in_definitions_come_from_drivers() {
+    let ids: Vec<String> = default_services().into_iter().map(|def| def.id).collect();
+    let driver_ids: Vec<&str> = drivers::all().iter().map(|driver| driver.id()).collect();
+    assert_eq!(ids, driver_ids);
+    assert!(drivers::find("redis").is_none());
+
+    let node = drivers::find("node").expect("node driver");
+    let source = node
+        .official_source("20.11.1", "linux", "x64")
+        .expect("node source");
+    assert_eq!(
+        source.url,
+        "https://nodejs.org/dist/v20.11.1/node-v20.11.1-linux-x64.tar.xz"
+    );
+    let php = drivers::find("php").expect("php driver");
+    assert!(php.official_source("8.3.2", "linux", "x64").is_none());
+}
+
+#[test]
+fn postgres_driver_initialises_data_once_and_dumps_through_its_tools() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let root = temp_dir.path();
+    let context = postgres_context(root, 5433);
+    let calls = root.join("calls.txt");
+    write_tool(
+        &root.join("bin"),
+        "initdb",
+        &format!(
+            "echo initdb \"$@\" >> {}; touch \"$2/PG_VERSION\"",
+            calls.display()
+        ),
+    );
+    write_tool(&root.join("bin"), "pg_dump", "echo \"$@\" > \"$8\"");
+
+    let driver = drivers::find("postgres").expect("postgres driver");
+    driver.init_data(&context).expect("init");
+    driver.init_data(&context).expect("second init");
+    assert_eq!(
+        fs::read_to_string(&calls).expect("calls"),
+        format!(
+            "initdb -D {} -U postgres --auth=trust\n",
+            context.data_dir.display()
+        )
+    );
+    assert!(context.data_dir.join("PG_VERSION").exists());
+
+    let output = root.join("app.sql");
+    driver.dump(&context, "app", &output).expect("dump");
+    // The rendered port, not the built-in default, reaches the tool.
+    assert_eq!(
+        fs::read_to_string(&output).expect("dump").trim(),
+        format!(
+            "-h 127.0.0.1 -p 5433 -U postgres -f {} app",
+            output.display()
+        )
+    );
+
+    let err = driver
+        .restore(&context, "app", &output)
+        .expect_err("no psql");
+    assert_eq!(err, "psql not found for postgres");
+    let mailpit = drivers::find("mailpit").expect("mailpit driver");
+    assert_eq!(
+        mailpit.dump(&context, "app", &output).unwrap_err(),
+        "mailpit does not support dumps"
+    );
+}
+
+#[test]
+fn postgres_starts_with_the_rendered_config_file() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let root = temp_dir.path();
+    let context = postgres_context(root, 5433);
+    let config_dir = root.join("runtime/config/postgres");
+    let config_file = format!(
+        "config_file={}",
+        config_dir.join("postgresql.conf").display()
+    );
+    assert!(context.def.args.contains(&config_file));
+
+    let driver = drivers::find("postgres").expect("postgres driver");
+    let (_, template) = driver
+        .config_templates()
+        .into_iter()
+        .find(|(name, _)| *name == "postgresql.conf.tmpl")
+        .expect("postgresql.conf template");
+    let rendered = TemplateContext::for_service(root, &context.def)
+        .render(template)
+        .expect("render");
+    assert!(rendered.contains("port=5433\n"));
+    assert!(rendered.contains(&format!(
+        "hba_file='{}'",
+        config_dir.join("pg_hba.conf").display()
+    )));
+    assert!(!rendered.contains("logging_collector"));
+}
+
+#[test]
+fn php_fpm_pool_listens_on_the_service_port() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let driver = drivers::find("php").expect("php driver");
+    let mut def = drivers::definition(driver.as_ref());
+    def.ports[0].port = 9100;
+    let (_, template) = driver
+        .config_templates()
+        .into_iter()
+        .find(|(name, _)| *name == "pool.d/www.conf.tmpl")
+        .expect("fpm pool template");
+    let rendered = TemplateContext::for_service(temp_dir.path(), &def)
+        .render(template)
+        .expect("render");
+    assert!(rendered.contains("listen=127.0.0.1:9100\n"));
+}
+
+#[test]
+fn tools_run_in_the_service_cwd_with_its_path() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let root = temp_dir.path();
+    let path = format!("{}:/usr/bin:/bin", root.join("runtime/bin").display());
+    let context = postgres_context(root, 5433).with_path(path.clone());
+    let seen = root.join("seen.txt");
+    write_tool(
+        &root.join("bin"),
+        "initdb",
+        &format!(
+            "echo \"$(pwd) $PATH\" > {}; touch \"$2/PG_VERSION\"",
+            seen.display()
+        ),
+    );
+
+    let driver = drivers::find("postgres").expect("postgres driver");
+    driver.init_data(&context).expect("init");
+    let cwd = fs::canonicalize(&context.def.cwd).expect("cwd");
+    assert_eq!(
+        fs::read_to_string(&seen).expect("seen").trim(),
+        format!("{} {path}", cwd.display())
+    );
+}
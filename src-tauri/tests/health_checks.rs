@@ -0,0 +1,128 @@
+mod common;
+
+use common::TestDefinition;
+use kojibox_lib::models::{
+    HealthCheck, HttpProbe, PortDef, RestartPolicy, ServiceDefinition, StatusRange,
+};
+use kojibox_lib::runtime::RuntimeManager;
+use kojibox_lib::service_manager::ServiceManager;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::thread;
+
+fn serve(status: &'static str, body: &'static str) -> u16 {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).expect("bind failed");
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let mut stream = stream;
+            let mut buffer = [0u8; 1024];
+            let _ = stream.read(&mut buffer);
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    port
+}
+
+fn http_service(port: u16, probe: HttpProbe) -> ServiceDefinition {
+    ServiceDefinition {
+        binary: "unused".to_string(),
+        ports: vec![PortDef {
+            name: "http".to_string(),
+            port,
+            protocol: "tcp".to_string(),
+        }],
+        health_check: HealthCheck {
+            kind: "http".to_string(),
+            target: format!("http://127.0.0.1:{port}/health"),
+            timeout_ms: 2000,
+            interval_ms: 200,
+            http: Some(probe),
+            command: Vec::new(),
+        },
+        restart_policy: RestartPolicy {
+            max_retries: 0,
+            backoff_ms: 100,
+            max_backoff_ms: 60_000,
+            window_ms: 300_000,
+        },
+        ..ServiceDefinition::test("web")
+    }
+}
+
+fn manager_for(def: ServiceDefinition) -> (ServiceManager, tempfile::TempDir) {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let runtime = RuntimeManager::new(".");
+    let manager = ServiceManager::new(runtime, vec![def], PathBuf::from(temp_dir.path()));
+    (manager, temp_dir)
+}
+
+#[test]
+fn http_health_reports_unexpected_status() {
+    let port = serve("503 Service Unavailable", "down");
+    let (manager, _dir) = manager_for(http_service(port, HttpProbe::default()));
+    let err = manager.health("web").expect_err("503 should be unhealthy");
+    assert_eq!(err, "503 from /health");
+}
+
+#[test]
+fn http_health_checks_status_range_and_body() {
+    let port = serve("200 OK", "{\"status\":\"ok\"}");
+    let probe = HttpProbe {
+        expected_status: StatusRange { from: 200, to: 200 },
+        body_contains: Some("ok".to_string()),
+        body_regex: Some("\"status\"\\s*:\\s*\"ok\"".to_string()),
+        ..HttpProbe::default()
+    };
+    let (manager, _dir) = manager_for(http_service(port, probe));
+    assert_eq!(manager.health("web").expect("healthy"), "ok");
+
+    let probe = HttpProbe {
+        body_contains: Some("ready".to_string()),
+        ..HttpProbe::default()
+    };
+    let (manager, _dir) = manager_for(http_service(port, probe));
+    let err = manager.health("web").expect_err("body mismatch");
+    assert!(err.contains("body missing \"ready\""), "{err}");
+}
+
+#[cfg(unix)]
+fn exec_service(script: &str, timeout_ms: u64) -> ServiceDefinition {
+    let mut def = http_service(4321, HttpProbe::default());
+    def.ports[0].name = "main".to_string();
+    def.health_check = HealthCheck {
+        kind: "exec".to_string(),
+        timeout_ms,
+        command: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+        ..HealthCheck::default()
+    };
+    def
+}
+
+#[cfg(unix)]
+#[test]
+fn exec_health_uses_exit_code_output_and_timeout() {
+    let (manager, _dir) = manager_for(exec_service("test ${port.main} = 4321", 2000));
+    assert_eq!(manager.health("web").expect("healthy"), "ok");
+
+    let script = "echo accepting; echo 'no response' >&2; exit 2";
+    let (manager, _dir) = manager_for(exec_service(script, 2000));
+    let err = manager.health("web").expect_err("exit 2 is unhealthy");
+    assert_eq!(err, "sh exited with code 2: no response");
+
+    // More output than a pipe buffer holds must not stall the check.
+    let script = "head -c 1000000 /dev/zero | tr '\\0' x; echo; echo done";
+    let (manager, _dir) = manager_for(exec_service(script, 2000));
+    assert_eq!(manager.health("web").expect("chatty check is healthy"), "ok");
+
+    let (manager, _dir) = manager_for(exec_service("sleep 5", 300));
+    let started = std::time::Instant::now();
+    let err = manager.health("web").expect_err("timeout");
+    assert!(err.contains("timed out after 300ms"), "{err}");
+    assert!(started.elapsed() < std::time::Duration::from_secs(2));
+}
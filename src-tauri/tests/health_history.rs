@@ -0,0 +1,145 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{stop_and_wait, TestDefinition};
+use kojibox_lib::health_history::{self, HealthProbe};
+use kojibox_lib::models::{HealthCheck, ServiceDefinition};
+use kojibox_lib::runtime::RuntimeManager;
+use kojibox_lib::service_manager::ServiceManager;
+use std::path::Path;
+use std::time::Duration;
+
+fn probe(ts: u64, latency_ms: u64, healthy: bool) -> HealthProbe {
+    HealthProbe {
+        ts,
+        latency_ms,
+        healthy,
+        error: (!healthy).then(|| "connection refused".to_string()),
+    }
+}
+
+// Healthy while `flag` exists.
+fn flagged_service(flag: &Path) -> ServiceDefinition {
+    ServiceDefinition {
+        health_check: HealthCheck {
+            kind: "exec".to_string(),
+            command: vec![
+                "test".to_string(),
+                "-e".to_string(),
+                flag.display().to_string(),
+            ],
+            timeout_ms: 2_000,
+            interval_ms: 50,
+            ..HealthCheck::default()
+        },
+        ..ServiceDefinition::shell("flagged", "while true; do sleep 0.05; done")
+    }
+}
+
+#[test]
+fn stats_cover_only_the_probes_inside_each_window() {
+    let probes = vec![
+        probe(1_000, 40, false),
+        probe(1_700, 10, true),
+        probe(1_800, 20, false),
+        probe(1_900, 30, true),
+        probe(1_950, 20, true),
+    ];
+    let report = health_history::report("postgres", &probes, 2_000, &[60, 300, 3_600], 0);
+
+    assert!(report.probes.is_empty());
+    let minute = &report.windows[0];
+    assert_eq!(minute.window_secs, 60);
+    assert_eq!(minute.probes, 1);
+    assert_eq!(minute.uptime_percent, Some(100.0));
+    assert_eq!(minute.flaps, 0);
+    let five = &report.windows[1];
+    assert_eq!(five.probes, 4);
+    assert_eq!(five.uptime_percent, Some(75.0));
+    assert_eq!(five.mean_latency_ms, Some(20.0));
+    assert_eq!(five.flaps, 2);
+    let hour = &report.windows[2];
+    assert_eq!(hour.probes, 5);
+    assert_eq!(hour.uptime_percent, Some(60.0));
+    assert_eq!(hour.flaps, 3);
+
+    let recent = health_history::report("postgres", &probes, 2_000, &[300], 2);
+    let ts: Vec<u64> = recent.probes.iter().map(|probe| probe.ts).collect();
+    assert_eq!(ts, vec![1_900, 1_950]);
+    let window = health_history::report("postgres", &probes, 2_000, &[300], 10);
+    assert_eq!(window.probes.len(), 4);
+
+    let empty = health_history::stats(&probes, 10_000, 60);
+    assert_eq!(empty.probes, 0);
+    assert_eq!(empty.uptime_percent, None);
+    assert_eq!(empty.mean_latency_ms, None);
+}
+
+#[test]
+fn ticks_record_probe_results_and_flaps() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let flag = temp_dir.path().join("healthy");
+    std::fs::write(&flag, "").expect("flag");
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new("."),
+        vec![flagged_service(&flag)],
+        temp_dir.path().join("logs"),
+    );
+    manager.start("flagged").expect("start");
+    let state = manager
+        .wait_for("flagged", Duration::from_secs(10))
+        .expect("state");
+    assert_eq!(state.state, "running");
+
+    manager.tick();
+    std::fs::remove_file(&flag).expect("remove flag");
+    manager.tick();
+    std::fs::write(&flag, "").expect("flag");
+    manager.tick();
+
+    let report = manager
+        .health_history("flagged", &[3_600], 10)
+        .expect("history");
+    let healthy: Vec<bool> = report.probes.iter().map(|probe| probe.healthy).collect();
+    assert_eq!(healthy, vec![true, false, true]);
+    assert!(report.probes[1]
+        .error
+        .as_deref()
+        .unwrap_or_default()
+        .contains("exit"));
+    let hour = &report.windows[0];
+    assert_eq!(hour.probes, 3);
+    assert_eq!(hour.flaps, 2);
+    assert!(hour.mean_latency_ms.is_some());
+    assert!(manager.health_history("redis", &[60], 0).is_err());
+    stop_and_wait(&mut manager, "flagged");
+}
+
+#[test]
+fn probes_of_a_service_stopped_meanwhile_are_not_recorded() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let flag = temp_dir.path().join("healthy");
+    std::fs::write(&flag, "").expect("flag");
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new("."),
+        vec![flagged_service(&flag)],
+        temp_dir.path().join("logs"),
+    );
+    manager.start("flagged").expect("start");
+    let state = manager
+        .wait_for("flagged", Duration::from_secs(10))
+        .expect("state");
+    assert_eq!(state.state, "running");
+
+    // The probes run after the manager lock would have been released.
+    let round = manager.begin_tick();
+    stop_and_wait(&mut manager, "flagged");
+    round.run();
+
+    let report = manager
+        .health_history("flagged", &[3_600], 10)
+        .expect("history");
+    assert!(report.probes.is_empty());
+    assert_eq!(manager.list()[0].state, "stopped");
+}
@@ -0,0 +1,120 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{stop_and_wait, TestDefinition};
+use kojibox_lib::models::{Hook, LifecycleHooks, ServiceDefinition};
+use kojibox_lib::runtime::RuntimeManager;
+use kojibox_lib::service_manager::ServiceManager;
+use std::collections::HashMap;
+use std::time::Duration;
+
+fn hook(script: &str, on_failure: &str, timeout_ms: u64) -> Hook {
+    Hook {
+        command: vec!["/bin/sh".to_string(), "-c".to_string(), script.to_string()],
+        timeout_ms,
+        on_failure: on_failure.to_string(),
+    }
+}
+
+fn hooked_service(hooks: LifecycleHooks) -> ServiceDefinition {
+    ServiceDefinition {
+        env: HashMap::from([("GREETING".to_string(), "hello".to_string())]),
+        hooks,
+        ..ServiceDefinition::shell("hooked", "while true; do sleep 0.05; done")
+    }
+}
+
+#[test]
+fn hooks_run_around_start_and_stop_with_captured_output() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let trail = temp_dir.path().join("trail.txt");
+    let append = |phase: &str| format!("echo {phase} >> {}", trail.display());
+    let hooks = LifecycleHooks {
+        pre_start: vec![hook(
+            &format!("echo \"$GREETING from pre-start\"; {}", append("pre-start")),
+            "abort",
+            5_000,
+        )],
+        post_start: vec![
+            hook(&append("post-start"), "abort", 5_000),
+            hook("echo slow; sleep 5", "warn", 200),
+        ],
+        pre_stop: vec![hook(&append("pre-stop"), "abort", 5_000)],
+        post_stop: vec![hook(&append("post-stop"), "abort", 5_000)],
+    };
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new("."),
+        vec![hooked_service(hooks)],
+        temp_dir.path().join("logs"),
+    );
+
+    manager.start("hooked").expect("start");
+    let state = manager
+        .wait_for("hooked", Duration::from_secs(10))
+        .expect("state");
+    assert_eq!(state.state, "running");
+    assert_eq!(
+        std::fs::read_to_string(&trail).expect("trail"),
+        "pre-start\npost-start\n"
+    );
+
+    let logs = manager.logs("hooked", 50);
+    let output = logs
+        .iter()
+        .find(|entry| entry.message == "hello from pre-start")
+        .expect("hook output in the service log");
+    assert_eq!(
+        output.fields.get("hook").map(String::as_str),
+        Some("pre-start")
+    );
+    // A "warn" hook that times out does not fail the start.
+    assert!(logs.iter().any(|entry| entry.level == "warn"
+        && entry.message == "post-start hook failed: /bin/sh timed out after 200ms"));
+
+    stop_and_wait(&mut manager, "hooked");
+    assert_eq!(
+        std::fs::read_to_string(&trail).expect("trail"),
+        "pre-start\npost-start\npre-stop\npost-stop\n"
+    );
+}
+
+#[test]
+fn failing_abort_hooks_fail_the_start() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new("."),
+        vec![hooked_service(LifecycleHooks {
+            pre_start: vec![hook("echo 'no database' >&2; exit 2", "abort", 5_000)],
+            ..LifecycleHooks::default()
+        })],
+        temp_dir.path().join("logs"),
+    );
+    manager.start("hooked").expect("start");
+    let state = manager
+        .wait_for("hooked", Duration::from_secs(10))
+        .expect("state");
+    assert_eq!(state.state, "error");
+    assert_eq!(state.pid, None);
+    assert_eq!(
+        state.last_error.as_deref(),
+        Some("pre-start hook failed: /bin/sh exited with code 2: no database")
+    );
+
+    // After a post-start failure the process is not left running.
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new("."),
+        vec![hooked_service(LifecycleHooks {
+            post_start: vec![hook("exit 1", "abort", 5_000)],
+            ..LifecycleHooks::default()
+        })],
+        temp_dir.path().join("logs"),
+    );
+    manager.start("hooked").expect("start");
+    let state = manager
+        .wait_for("hooked", Duration::from_secs(10))
+        .expect("state");
+    assert_eq!(state.state, "error");
+    assert_eq!(state.pid, None);
+    assert!(manager.tracked_pids().is_empty());
+}
@@ -1,14 +1,11 @@
+mod common;
+
+use common::{free_port, stop_and_wait, TestDefinition};
 use kojibox_lib::models::{HealthCheck, PortDef, RestartPolicy, ServiceDefinition};
 use kojibox_lib::runtime::RuntimeManager;
 use kojibox_lib::service_manager::ServiceManager;
-use std::collections::HashMap;
-use std::net::TcpListener;
 use std::path::PathBuf;
-
-fn free_port() -> u16 {
-    let listener = TcpListener::bind(("127.0.0.1", 0)).expect("bind failed");
-    listener.local_addr().unwrap().port()
-}
+use std::time::Duration;
 
 #[test]
 fn integration_dependency_start() {
@@ -18,37 +15,33 @@ fn integration_dependency_start() {
     assert_ne!(db_port, app_port);
 
     let db = ServiceDefinition {
-        id: "postgres".to_string(),
-        name: "Postgres".to_string(),
         binary: bin.clone(),
         args: vec![db_port.to_string()],
-        env: HashMap::new(),
-        cwd: ".".to_string(),
         ports: vec![PortDef {
             name: "db".to_string(),
             port: db_port,
             protocol: "tcp".to_string(),
         }],
-        depends_on: Vec::new(),
         health_check: HealthCheck {
             kind: "port".to_string(),
             target: format!("127.0.0.1:{db_port}"),
             timeout_ms: 2000,
             interval_ms: 200,
+            http: None,
+            command: Vec::new(),
         },
         restart_policy: RestartPolicy {
             max_retries: 1,
             backoff_ms: 100,
+            max_backoff_ms: 60_000,
+            window_ms: 300_000,
         },
+        ..ServiceDefinition::test("postgres")
     };
 
     let app = ServiceDefinition {
-        id: "app".to_string(),
-        name: "App".to_string(),
         binary: bin,
         args: vec![app_port.to_string()],
-        env: HashMap::new(),
-        cwd: ".".to_string(),
         ports: vec![PortDef {
             name: "http".to_string(),
             port: app_port,
@@ -60,11 +53,16 @@ fn integration_dependency_start() {
             target: format!("127.0.0.1:{app_port}"),
             timeout_ms: 2000,
             interval_ms: 200,
+            http: None,
+            command: Vec::new(),
         },
         restart_policy: RestartPolicy {
             max_retries: 1,
             backoff_ms: 100,
+            max_backoff_ms: 60_000,
+            window_ms: 300_000,
         },
+        ..ServiceDefinition::test("app")
     };
 
     let temp_dir = tempfile::tempdir().expect("tempdir");
@@ -73,9 +71,89 @@ fn integration_dependency_start() {
 
     let state = manager.start("app").expect("start app");
     assert!(state.state == "running" || state.state == "starting");
+    let _ = manager.wait_for("postgres", Duration::from_secs(10));
+    let _ = manager.wait_for("app", Duration::from_secs(10));
     assert!(manager.health("postgres").is_ok());
     assert!(manager.health("app").is_ok());
 
-    let _ = manager.stop("app");
-    let _ = manager.stop("postgres");
+    stop_and_wait(&mut manager, "app");
+    stop_and_wait(&mut manager, "postgres");
+}
+
+fn dummy_service(id: &str, binary: &str, port: u16, depends_on: &[&str]) -> ServiceDefinition {
+    ServiceDefinition {
+        binary: binary.to_string(),
+        args: vec![port.to_string()],
+        ports: vec![PortDef {
+            name: "main".to_string(),
+            port,
+            protocol: "tcp".to_string(),
+        }],
+        depends_on: depends_on.iter().map(|dep| dep.to_string()).collect(),
+        dependency_timeout_ms: 10_000,
+        health_check: HealthCheck {
+            kind: "port".to_string(),
+            target: format!("127.0.0.1:{port}"),
+            timeout_ms: 1000,
+            interval_ms: 200,
+            http: None,
+            command: Vec::new(),
+        },
+        restart_policy: RestartPolicy {
+            max_retries: 0,
+            backoff_ms: 100,
+            max_backoff_ms: 60_000,
+            window_ms: 300_000,
+        },
+        ..ServiceDefinition::test(id)
+    }
+}
+
+#[test]
+fn integration_failed_dependency_blocks_dependents() {
+    let bin = std::env::var("CARGO_BIN_EXE_dummy_service").expect("dummy service bin");
+    let services = vec![
+        dummy_service("db", "missing/bin/db", free_port(), &[]),
+        dummy_service("api", &bin, free_port(), &["db"]),
+        dummy_service("web", &bin, free_port(), &["api"]),
+    ];
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let runtime = RuntimeManager::new(".");
+    let mut manager = ServiceManager::new(runtime, services, PathBuf::from(temp_dir.path()));
+
+    assert_eq!(manager.start_plan("web").unwrap(), vec!["db", "api", "web"]);
+    assert_eq!(manager.dependents("db"), vec!["web", "api"]);
+
+    manager.start("web").expect("start web");
+    let web = manager.wait_for("web", Duration::from_secs(10)).expect("web state");
+    assert_eq!(web.state, "blocked");
+    assert_eq!(web.blocked_by.as_deref(), Some("api"));
+    let api = manager.state("api").expect("api state");
+    assert_eq!(api.state, "blocked");
+    assert_eq!(api.blocked_by.as_deref(), Some("db"));
+    assert_eq!(manager.state("db").expect("db state").state, "error");
+}
+
+#[test]
+fn integration_stop_with_dependents_stops_in_reverse_order() {
+    let bin = std::env::var("CARGO_BIN_EXE_dummy_service").expect("dummy service bin");
+    let services = vec![
+        dummy_service("db", &bin, free_port(), &[]),
+        dummy_service("api", &bin, free_port(), &["db"]),
+    ];
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let runtime = RuntimeManager::new(".");
+    let mut manager = ServiceManager::new(runtime, services, PathBuf::from(temp_dir.path()));
+
+    manager.start("api").expect("start api");
+    let api = manager.wait_for("api", Duration::from_secs(10)).expect("api state");
+    assert_eq!(api.state, "running");
+    assert_eq!(manager.state("db").expect("db state").state, "running");
+
+    let db = manager.stop_with_dependents("db").expect("stop db");
+    assert_eq!(db.state, "stopping");
+    let db = manager.wait_for("db", Duration::from_secs(30)).expect("db state");
+    assert_eq!(db.state, "stopped");
+    // The dependent went down before the service it depends on.
+    assert_eq!(manager.state("api").expect("api state").state, "stopped");
 }
@@ -0,0 +1,102 @@
+#![cfg(unix)]
+
+use kojibox_lib::config::default_services;
+use kojibox_lib::config_store::ServiceConfig;
+use kojibox_lib::runtime::RuntimeManager;
+use kojibox_lib::secrets::SecretsStore;
+use kojibox_lib::service_manager::ServiceManager;
+use std::collections::HashMap;
+use std::fs;
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn launch_plan_resolves_overrides_without_side_effects() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let root = std::path::absolute(temp_dir.path()).expect("absolute root");
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new(&root),
+        default_services(),
+        root.join("logs"),
+    );
+    let mut secrets = SecretsStore::new(&root).expect("secrets");
+    secrets.set("pg_password", "hunter2").expect("set secret");
+    manager.set_secrets(Arc::new(Mutex::new(secrets)));
+
+    let config_dir = root.join("runtime/config/postgres");
+    fs::create_dir_all(&config_dir).expect("config dir");
+    fs::write(
+        config_dir.join("postgresql.conf.tmpl"),
+        "port=${port.main}\n",
+    )
+    .expect("template");
+
+    let busy = TcpListener::bind(("127.0.0.1", 0)).expect("bind");
+    let port = busy.local_addr().expect("addr").port();
+    let config = ServiceConfig {
+        id: "postgres".to_string(),
+        ports: HashMap::from([("main".to_string(), port)]),
+        env: HashMap::from([(
+            "PGPASSWORD".to_string(),
+            "${secret:pg_password}".to_string(),
+        )]),
+        args: vec!["-c".to_string(), "log_connections=on".to_string()],
+        ..ServiceConfig::default()
+    };
+    let plan = manager
+        .launch_plan("postgres", &config)
+        .expect("launch plan");
+
+    let data_dir = root.join("runtime/data/postgres");
+    let bin_dir = root.join("runtime/bin/postgres");
+    assert!(plan
+        .binary
+        .starts_with(&bin_dir.to_string_lossy().to_string()));
+    assert!(!plan.binary_found);
+    assert_eq!(
+        plan.args,
+        vec![
+            "-D".to_string(),
+            data_dir.to_string_lossy().to_string(),
+            "-p".to_string(),
+            port.to_string(),
+            "-c".to_string(),
+            format!(
+                "config_file={}",
+                root.join("runtime/config/postgres/postgresql.conf")
+                    .display()
+            ),
+            "-c".to_string(),
+            "log_connections=on".to_string(),
+        ]
+    );
+    assert_eq!(plan.cwd, root.join("runtime").to_string_lossy());
+    assert_eq!(plan.env["PGPASSWORD"], "********");
+    assert_eq!(plan.env["PGDATA"], data_dir.to_string_lossy());
+    let binary_dir = std::path::Path::new(&plan.binary).parent().unwrap();
+    assert!(plan.env["PATH"].starts_with(&binary_dir.to_string_lossy().to_string()));
+    assert_eq!(plan.ports[0].port, port);
+    assert_eq!(
+        plan.health_check.command,
+        vec!["pg_isready", "-h", "127.0.0.1", "-p", &port.to_string()]
+    );
+    assert_eq!(plan.depends_on, Vec::<String>::new());
+    assert_eq!(
+        plan.generated_files,
+        vec![config_dir
+            .join("postgresql.conf")
+            .to_string_lossy()
+            .to_string()]
+    );
+    assert!(plan.warnings[0].starts_with("binary not found: "));
+    assert_eq!(
+        plan.warnings[1],
+        format!("port main ({port}) is already in use")
+    );
+
+    // Nothing was rendered, spawned or logged.
+    assert!(!config_dir.join("postgresql.conf").exists());
+    assert!(manager.state("postgres").is_none());
+    assert!(manager.logs("postgres", 10).is_empty());
+    assert!(manager.launch_plan("redis", &config).is_err());
+}
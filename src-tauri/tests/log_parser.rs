@@ -0,0 +1,78 @@
+use kojibox_lib::log_parser::parse_line;
+
+#[test]
+fn postgres_lines_use_severity_pid_and_sql_state() {
+    let ready = parse_line(
+        Some("postgres"),
+        "error",
+        "2024-05-01 10:00:00.123 UTC [4242] LOG:  database system is ready to accept connections",
+    );
+    assert_eq!(ready.level, "info");
+    assert_eq!(ready.message, "database system is ready to accept connections");
+    assert_eq!(ready.fields["pid"], "4242");
+    assert_eq!(ready.fields["time"], "2024-05-01 10:00:00.123 UTC");
+
+    let failed = parse_line(
+        Some("postgres"),
+        "error",
+        "2024-05-01 10:00:01.000 UTC [4243] ERROR:  42P01: relation \"users\" does not exist",
+    );
+    assert_eq!(failed.level, "error");
+    assert_eq!(failed.fields["sqlState"], "42P01");
+    assert_eq!(failed.message, "relation \"users\" does not exist");
+}
+
+#[test]
+fn mariadb_and_php_lines_are_classified() {
+    let note = parse_line(
+        Some("mariadb"),
+        "error",
+        "2024-05-01 10:00:00 0 [Note] InnoDB: Buffer pool(s) load completed",
+    );
+    assert_eq!(note.level, "info");
+    assert_eq!(note.fields["subsystem"], "InnoDB");
+    assert_eq!(note.fields["thread"], "0");
+
+    let missing = parse_line(
+        Some("php-server"),
+        "error",
+        "[Wed May  1 10:00:00 2024] 127.0.0.1:51234 [404]: GET /missing - No such file or directory",
+    );
+    assert_eq!(missing.level, "warn");
+    assert_eq!(missing.fields["status"], "404");
+    assert_eq!(missing.fields["path"], "/missing");
+    assert_eq!(missing.fields["method"], "GET");
+    assert_eq!(missing.fields["time"], "Wed May  1 10:00:00 2024");
+
+    let accepted = parse_line(
+        Some("php-server"),
+        "error",
+        "[Wed May  1 10:00:00 2024] 127.0.0.1:51234 Accepted",
+    );
+    assert_eq!(accepted.level, "info");
+}
+
+#[test]
+fn json_and_logfmt_lines_fill_fields() {
+    let json = parse_line(
+        Some("json"),
+        "info",
+        r#"{"level":"warn","time":"2024-05-01T10:00:00Z","msg":"slow request","status":200}"#,
+    );
+    assert_eq!(json.level, "warn");
+    assert_eq!(json.message, "slow request");
+    assert_eq!(json.fields["status"], "200");
+    assert_eq!(json.fields["time"], "2024-05-01T10:00:00Z");
+
+    let mailpit = parse_line(
+        Some("mailpit"),
+        "error",
+        r#"time="2024/05/01 10:00:00" level=info msg="[smtpd] starting on [::]:1025""#,
+    );
+    assert_eq!(mailpit.level, "info");
+    assert_eq!(mailpit.message, "[smtpd] starting on [::]:1025");
+
+    let plain = parse_line(Some("json"), "error", "not json at all");
+    assert_eq!(plain.level, "error");
+    assert!(plain.fields.is_empty());
+}
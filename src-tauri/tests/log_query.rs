@@ -0,0 +1,211 @@
+mod common;
+
+use common::TestDefinition;
+use kojibox_lib::config_store::ServiceConfig;
+use kojibox_lib::log_query::{format_millis, query, ts_millis, LogQuery};
+use kojibox_lib::models::{LogEntry, ServiceDefinition};
+use kojibox_lib::runtime::RuntimeManager;
+use kojibox_lib::service_manager::ServiceManager;
+use std::collections::HashMap;
+
+fn entry(ts: &str, level: &str, service: &str, message: &str) -> LogEntry {
+    LogEntry {
+        ts: ts.to_string(),
+        seq: 0,
+        level: level.to_string(),
+        service: service.to_string(),
+        message: message.to_string(),
+        fields: HashMap::new(),
+    }
+}
+
+fn write_logs(root: &std::path::Path) {
+    std::fs::write(
+        root.join("postgres.log.2"),
+        "1000 [info] starting\n1010 [error] deadlock detected\n",
+    )
+    .expect("write rotation");
+    std::fs::write(
+        root.join("postgres.log.1"),
+        "2000 [warn] checkpoint too frequent\n",
+    )
+    .expect("write rotation");
+    std::fs::write(
+        root.join("postgres.log"),
+        "3000 [info] ready\n3000 [error] connection timeout\n",
+    )
+    .expect("write active");
+    std::fs::write(root.join("mailpit.log"), "2500 [error] smtp timeout\n").expect("write mailpit");
+}
+
+#[test]
+fn query_searches_rotations_and_memory_without_duplicates() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    write_logs(temp_dir.path());
+    let mut memory = HashMap::new();
+    let mut ready = entry("3000", "info", "postgres", "ready");
+    ready.fields.insert("pid".to_string(), "42".to_string());
+    memory.insert(
+        "postgres".to_string(),
+        vec![
+            ready,
+            entry("3000", "error", "postgres", "connection timeout"),
+        ],
+    );
+
+    let page = query(temp_dir.path(), memory.clone(), &LogQuery::default()).expect("query");
+    let messages: Vec<&str> = page.entries.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages.len(), 6);
+    assert_eq!(messages.iter().filter(|m| **m == "ready").count(), 1);
+    assert_eq!(page.entries.last().unwrap().message, "starting");
+    let ready = page.entries.iter().find(|e| e.message == "ready").unwrap();
+    assert_eq!(ready.fields["pid"], "42");
+
+    let filter = LogQuery {
+        services: vec!["postgres".to_string()],
+        text: Some("deadlock|timeout".to_string()),
+        regex: true,
+        min_level: Some("error".to_string()),
+        to: Some("2999".to_string()),
+        ..LogQuery::default()
+    };
+    let page = query(temp_dir.path(), memory, &filter).expect("query");
+    assert_eq!(page.entries.len(), 1);
+    assert_eq!(page.entries[0].message, "deadlock detected");
+}
+
+#[test]
+fn query_pages_with_a_stable_cursor() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    write_logs(temp_dir.path());
+    let mut seen = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = query(
+            temp_dir.path(),
+            HashMap::new(),
+            &LogQuery {
+                cursor: cursor.clone(),
+                limit: 2,
+                ..LogQuery::default()
+            },
+        )
+        .expect("query");
+        seen.extend(page.entries.into_iter().map(|e| e.message));
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+    assert_eq!(
+        seen,
+        vec![
+            "connection timeout",
+            "ready",
+            "smtp timeout",
+            "checkpoint too frequent",
+            "deadlock detected",
+            "starting",
+        ]
+    );
+}
+
+#[test]
+fn timestamps_accept_unix_seconds_and_rfc3339() {
+    assert_eq!(ts_millis("1714557600"), Some(1_714_557_600_000));
+    assert_eq!(ts_millis("2024-05-01T10:00:00Z"), Some(1_714_557_600_000));
+    assert_eq!(
+        ts_millis("2024-05-01T10:00:00.250Z"),
+        Some(1_714_557_600_250)
+    );
+    assert_eq!(
+        ts_millis("2024-05-01T12:00:00+02:00"),
+        Some(1_714_557_600_000)
+    );
+    assert!(query(
+        std::path::Path::new("."),
+        HashMap::new(),
+        &LogQuery {
+            from: Some("yesterday".to_string()),
+            ..LogQuery::default()
+        }
+    )
+    .is_err());
+}
+
+#[test]
+fn timestamps_format_as_rfc3339_millis() {
+    assert_eq!(format_millis(1_714_557_600_250), "2024-05-01T10:00:00.250Z");
+    assert_eq!(format_millis(951_782_400_000), "2000-02-29T00:00:00.000Z");
+    assert_eq!(
+        ts_millis(&format_millis(1_714_557_600_250)),
+        Some(1_714_557_600_250)
+    );
+}
+
+#[test]
+fn manager_log_entries_carry_millis_and_sequence() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new("."),
+        kojibox_lib::config::default_services(),
+        temp_dir.path().join("logs"),
+    );
+    for _ in 0..3 {
+        manager
+            .apply_config_no_restart("postgres", ServiceConfig::default())
+            .expect("apply");
+    }
+    let logs = manager.logs("postgres", 10);
+    let seqs: Vec<u64> = logs.iter().map(|entry| entry.seq).collect();
+    assert_eq!(seqs, vec![1, 2, 3]);
+    for entry in &logs {
+        assert_eq!(entry.ts.len(), "2024-05-01T10:00:00.250Z".len());
+        assert!(ts_millis(&entry.ts).is_some());
+    }
+
+    let snapshot = manager.log_snapshot("postgres", 2);
+    let seqs: Vec<u64> = snapshot.entries.iter().map(|entry| entry.seq).collect();
+    assert_eq!(seqs, vec![2, 3]);
+    assert_eq!(snapshot.last_seq, 3);
+    assert_eq!(manager.log_snapshot("postgres", 0).last_seq, 3);
+    assert_eq!(manager.log_snapshot("mailpit", 10).last_seq, 0);
+
+    let page = manager.query_logs(&LogQuery::default()).expect("query");
+    assert_eq!(page.entries.len(), 3);
+}
+
+#[cfg(unix)]
+#[test]
+fn manager_log_entries_keep_the_service_time_as_a_field() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let def = ServiceDefinition {
+        log_format: Some("json".to_string()),
+        ..ServiceDefinition::shell(
+            "json",
+            "echo '{\"level\":\"warn\",\"time\":1714557600000,\"msg\":\"slow\"}'; sleep 300",
+        )
+    };
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new("."),
+        vec![def],
+        temp_dir.path().join("logs"),
+    );
+    let started = kojibox_lib::log_query::now_millis();
+    manager.start("json").expect("start json");
+    let mut slow = None;
+    for _ in 0..50 {
+        slow = manager
+            .logs("json", 100)
+            .into_iter()
+            .find(|entry| entry.message == "slow");
+        if slow.is_some() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    let slow = slow.expect("slow");
+    assert_eq!(slow.fields["time"], "1714557600000");
+    assert!(ts_millis(&slow.ts).expect("ts") >= started);
+    common::stop_and_wait(&mut manager, "json");
+}
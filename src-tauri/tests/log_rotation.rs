@@ -0,0 +1,181 @@
+mod common;
+
+use common::TestDefinition;
+use kojibox_lib::config_store::LogRotation;
+use kojibox_lib::log_files::{read_log, rotated_files, LogFiles};
+use std::fs;
+
+fn policy(max_files: u32, compress: bool) -> LogRotation {
+    LogRotation {
+        max_size_bytes: 64,
+        max_files,
+        max_age_days: 0,
+        compress,
+    }
+}
+
+#[test]
+fn rotation_compresses_and_caps_rotated_files() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let files = LogFiles::new(temp_dir.path().join("services"));
+    files.set_policy("postgres", policy(2, true));
+
+    for index in 0..12 {
+        files
+            .append("postgres", &format!("1000 [info] line number {index:02}"))
+            .expect("append");
+    }
+
+    files.flush("postgres");
+    let active = files.active_path("postgres");
+    let rotated = rotated_files(&active);
+    assert_eq!(rotated.len(), 2);
+    assert!(rotated
+        .iter()
+        .all(|path| path.extension().and_then(|ext| ext.to_str()) == Some("gz")));
+    let newest = read_log(rotated.last().unwrap()).expect("read gz");
+    assert!(newest.contains("[info] line number"));
+    assert!(fs::metadata(&active).unwrap().len() <= 64);
+
+    files
+        .append("postgres", "1000 [info] buffered before clear")
+        .expect("append");
+    files.clear("postgres").expect("clear");
+    assert_eq!(fs::read_to_string(&active).expect("read active"), "");
+    assert!(rotated_files(&active).is_empty());
+}
+
+#[test]
+fn disk_budget_removes_oldest_rotations_but_keeps_active_logs() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let logs_root = temp_dir.path().join("logs");
+    let files = LogFiles::new(logs_root.join("services"));
+    files.set_policy("mailpit", policy(5, false));
+    for index in 0..20 {
+        files
+            .append(
+                "mailpit",
+                &format!("1000 [info] message {index:02} padding"),
+            )
+            .expect("append");
+    }
+    files.flush("mailpit");
+    let active = files.active_path("mailpit");
+    let before = rotated_files(&active);
+    assert!(before.len() > 2);
+
+    let freed = files.enforce_disk_budget(&logs_root, 150);
+    assert!(freed > 0);
+    let after = rotated_files(&active);
+    assert!(after.len() < before.len());
+    // The newest rotation survives; deletion starts from the oldest.
+    assert!(after.contains(before.last().unwrap()));
+    assert!(active.exists());
+}
+
+#[test]
+fn disk_budget_only_removes_rotated_service_logs() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let logs_root = temp_dir.path().join("logs");
+    let services = logs_root.join("services");
+    let files = LogFiles::new(services.clone());
+    fs::create_dir_all(services.join("capture")).expect("capture dir");
+    let padding = "x".repeat(100);
+    let kept = [
+        logs_root.join("app.log.1"),
+        logs_root.join("export.zip"),
+        services.join("capture").join("redis.stdout"),
+        services.join("notes.txt"),
+        services.join("redis.log"),
+    ];
+    for path in &kept {
+        fs::write(path, &padding).expect("write kept file");
+    }
+    let rotated = [services.join("redis.log.2.gz"), services.join("redis.log.1")];
+    for path in &rotated {
+        fs::write(path, &padding).expect("write rotation");
+    }
+
+    let freed = files.enforce_disk_budget(&logs_root, 0);
+    assert_eq!(freed, 200);
+    for path in &rotated {
+        assert!(!path.exists(), "{} was kept", path.display());
+    }
+    for path in &kept {
+        assert!(path.exists(), "{} was removed", path.display());
+    }
+}
+
+#[test]
+fn writer_flushes_queued_lines_on_its_own() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let files = LogFiles::new(temp_dir.path().join("services"));
+    for index in 0..100 {
+        files
+            .append("node", &format!("1000 [info] vite update {index}"))
+            .expect("append");
+    }
+    let active = files.active_path("node");
+    let mut lines = 0;
+    for _ in 0..40 {
+        lines = fs::read_to_string(&active)
+            .map(|raw| raw.lines().count())
+            .unwrap_or(0);
+        if lines == 100 {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    assert_eq!(lines, 100);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn capture_files_give_back_read_output_without_losing_lines() {
+    use kojibox_lib::log_query::LogQuery;
+    use kojibox_lib::models::ServiceDefinition;
+    use kojibox_lib::runtime::RuntimeManager;
+    use kojibox_lib::service_manager::ServiceManager;
+    use std::os::unix::fs::MetadataExt;
+
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    // About 10 MB, more than a capture file keeps allocated.
+    let script = "pad=$(printf '%01000d' 0); i=0; \
+        while [ $i -lt 10000 ]; do echo \"line $i $pad\"; i=$((i+1)); done; sleep 300";
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new("."),
+        vec![ServiceDefinition::shell("chatty", script)],
+        temp_dir.path().join("logs"),
+    );
+    manager.start("chatty").expect("start chatty");
+    let mut done = false;
+    for _ in 0..200 {
+        done = manager
+            .logs("chatty", 1)
+            .iter()
+            .any(|entry| entry.message.starts_with("line 9999 "));
+        if done {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    assert!(done, "last line never arrived");
+
+    let page = manager
+        .query_logs(&LogQuery {
+            services: vec!["chatty".to_string()],
+            text: Some("line ".to_string()),
+            limit: 20_000,
+            ..LogQuery::default()
+        })
+        .expect("query");
+    assert_eq!(page.entries.len(), 10_000);
+    let capture =
+        fs::metadata(temp_dir.path().join("logs/capture/chatty.stdout")).expect("capture");
+    assert!(capture.len() > 10_000_000);
+    assert!(
+        capture.blocks() * 512 < capture.len() / 2,
+        "read output was not reclaimed"
+    );
+    common::stop_and_wait(&mut manager, "chatty");
+}
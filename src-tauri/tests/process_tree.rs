@@ -0,0 +1,136 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{stop_and_wait, TestDefinition};
+use kojibox_lib::models::{HealthCheck, RestartPolicy, ServiceDefinition};
+use kojibox_lib::runtime::RuntimeManager;
+use kojibox_lib::service_manager::ServiceManager;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use sysinfo::{Pid, System};
+
+fn children_of(pid: u32) -> Vec<Pid> {
+    let mut system = System::new();
+    system.refresh_processes();
+    system
+        .processes()
+        .iter()
+        .filter(|(_, process)| {
+            process.thread_kind().is_none() && process.parent() == Some(Pid::from_u32(pid))
+        })
+        .map(|(child, _)| *child)
+        .collect()
+}
+
+fn is_running(pid: Pid) -> bool {
+    let mut system = System::new();
+    system.refresh_process(pid)
+        && system
+            .process(pid)
+            .is_some_and(|process| process.status() != sysinfo::ProcessStatus::Zombie)
+}
+
+#[test]
+fn stop_kills_grandchildren_left_behind_by_the_root_process() {
+    let def = ServiceDefinition {
+        args: vec!["-c".to_string(), "sleep 300 & sleep 300 & wait".to_string()],
+        health_check: HealthCheck {
+            kind: "pid".to_string(),
+            target: String::new(),
+            timeout_ms: 1000,
+            interval_ms: 100,
+            http: None,
+            command: Vec::new(),
+        },
+        restart_policy: RestartPolicy {
+            max_retries: 0,
+            backoff_ms: 100,
+            max_backoff_ms: 60_000,
+            window_ms: 300_000,
+        },
+        ..ServiceDefinition::test("tree")
+    };
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let runtime = RuntimeManager::new(".");
+    let mut manager = ServiceManager::new(runtime, vec![def], PathBuf::from(temp_dir.path()));
+
+    manager.start("tree").expect("start tree");
+    let state = manager
+        .wait_for("tree", Duration::from_secs(10))
+        .expect("state");
+    let root = state.pid.expect("root pid");
+    let mut sleepers = Vec::new();
+    for _ in 0..50 {
+        sleepers = children_of(root);
+        if sleepers.len() == 2 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert_eq!(sleepers.len(), 2);
+
+    stop_and_wait(&mut manager, "tree");
+    for sleeper in sleepers {
+        assert!(!is_running(sleeper), "grandchild {sleeper} survived stop");
+    }
+    assert!(manager.leaked_processes().is_empty());
+}
+
+#[test]
+fn stop_kills_daemonized_grandchildren_that_ignore_the_stop_signal() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let pid_file = temp_dir.path().join("daemon.pid");
+    // The subshell exits right away, so the sleeper is reparented out of the
+    // service's tree before stop() looks at it.
+    let script = format!(
+        "(trap '' TERM; sleep 300 & echo $! > {pid_file}); while true; do sleep 0.05; done",
+        pid_file = pid_file.display()
+    );
+    let def = ServiceDefinition {
+        health_check: HealthCheck {
+            kind: "pid".to_string(),
+            interval_ms: 100,
+            ..HealthCheck::default()
+        },
+        ..ServiceDefinition::shell("daemon", &script)
+    };
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new("."),
+        vec![def],
+        temp_dir.path().join("logs"),
+    );
+
+    manager.start("daemon").expect("start daemon");
+    let state = manager
+        .wait_for("daemon", Duration::from_secs(10))
+        .expect("state");
+    let root = state.pid.expect("root pid");
+    let mut daemon = None;
+    for _ in 0..50 {
+        daemon = std::fs::read_to_string(&pid_file)
+            .ok()
+            .and_then(|raw| raw.trim().parse::<u32>().ok());
+        if daemon.is_some() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    let daemon = Pid::from_u32(daemon.expect("daemon pid"));
+    assert!(is_running(daemon));
+    assert!(!children_of(root).contains(&daemon));
+
+    stop_and_wait(&mut manager, "daemon");
+    // SIGKILL is delivered asynchronously.
+    for _ in 0..20 {
+        if !is_running(daemon) {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert!(
+        !is_running(daemon),
+        "daemonized grandchild {daemon} survived stop"
+    );
+}
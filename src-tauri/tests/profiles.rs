@@ -0,0 +1,141 @@
+mod common;
+
+use common::{free_port, stop_and_wait, TestDefinition};
+use kojibox_lib::config_store::{ConfigStore, Profile, ProfileService, ServiceConfig};
+use kojibox_lib::models::{HealthCheck, PortDef, RestartPolicy, ServiceDefinition};
+use kojibox_lib::runtime::RuntimeManager;
+use kojibox_lib::service_manager::ServiceManager;
+use std::collections::HashMap;
+use std::time::Duration;
+
+fn dummy(id: &str, depends_on: &[&str]) -> ServiceDefinition {
+    let bin = std::env::var("CARGO_BIN_EXE_dummy_service").expect("dummy service bin");
+    let port = free_port();
+    ServiceDefinition {
+        binary: bin,
+        args: vec![port.to_string()],
+        ports: vec![PortDef {
+            name: "main".to_string(),
+            port,
+            protocol: "tcp".to_string(),
+        }],
+        depends_on: depends_on.iter().map(|dep| dep.to_string()).collect(),
+        health_check: HealthCheck {
+            kind: "port".to_string(),
+            target: format!("127.0.0.1:{port}"),
+            timeout_ms: 2000,
+            interval_ms: 200,
+            http: None,
+            command: Vec::new(),
+        },
+        restart_policy: RestartPolicy {
+            max_retries: 0,
+            ..RestartPolicy::default()
+        },
+        ..ServiceDefinition::test(id)
+    }
+}
+
+fn member(id: &str) -> ServiceConfig {
+    ServiceConfig {
+        id: id.to_string(),
+        ..ServiceConfig::default()
+    }
+}
+
+#[test]
+fn profiles_round_trip_and_track_the_active_profile() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let store = ConfigStore::new(temp_dir.path());
+    let profile = Profile {
+        schema_version: 1,
+        name: "laravel".to_string(),
+        services: vec![
+            ProfileService {
+                id: "php".to_string(),
+                version: Some("8.3.0".to_string()),
+                ports: HashMap::new(),
+                env: HashMap::from([("APP_ENV".to_string(), "local".to_string())]),
+            },
+            ProfileService {
+                id: "mariadb".to_string(),
+                version: None,
+                ports: HashMap::from([("main".to_string(), 3307)]),
+                env: HashMap::new(),
+            },
+        ],
+    };
+    store.save_profile(&profile).expect("save");
+    let loaded = store.load_profile("laravel").expect("load");
+    assert_eq!(loaded.services.len(), 2);
+    assert_eq!(store.list_profiles().expect("list").len(), 1);
+
+    let mut config = member("mariadb");
+    config.ports.insert("main".to_string(), 3306);
+    loaded.services[1].apply(&mut config);
+    assert_eq!(config.ports["main"], 3307);
+
+    let mut bad = profile.clone();
+    bad.name = "Not A Name".to_string();
+    assert!(store.save_profile(&bad).is_err());
+    bad.name = "dup".to_string();
+    bad.services.push(bad.services[0].clone());
+    assert!(store
+        .save_profile(&bad)
+        .unwrap_err()
+        .contains("duplicate service"));
+
+    store.set_active_profile(Some("laravel")).expect("activate");
+    assert_eq!(
+        store.load_app_config_or_default().active_profile.as_deref(),
+        Some("laravel")
+    );
+    store.delete_profile("laravel").expect("delete");
+    assert_eq!(store.load_app_config_or_default().active_profile, None);
+}
+
+#[test]
+fn activating_a_profile_starts_members_and_stops_everything_else() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let definitions = vec![
+        dummy("db", &[]),
+        dummy("web", &["db"]),
+        dummy("mail", &[]),
+        dummy("worker", &["mail"]),
+    ];
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new("."),
+        definitions,
+        temp_dir.path().join("logs"),
+    );
+    manager.start("worker").expect("start worker");
+    let state = manager
+        .wait_for("worker", Duration::from_secs(10))
+        .expect("worker");
+    assert_eq!(state.state, "running");
+
+    let states = manager
+        .activate_profile(vec![member("web")])
+        .expect("activate");
+    assert_eq!(states.len(), 1);
+    for id in ["db", "web"] {
+        let state = manager.wait_for(id, Duration::from_secs(10)).expect(id);
+        assert_eq!(state.state, "running", "{id}");
+    }
+    for id in ["mail", "worker"] {
+        let state = manager.wait_for(id, Duration::from_secs(10)).expect(id);
+        assert_eq!(state.state, "stopped", "{id}");
+    }
+
+    // Activating again with the same config leaves running members alone.
+    let pid = manager.state("web").and_then(|state| state.pid);
+    manager
+        .activate_profile(vec![member("web")])
+        .expect("reactivate");
+    assert_eq!(manager.state("web").and_then(|state| state.pid), pid);
+
+    assert!(manager.activate_profile(vec![member("missing")]).is_err());
+    for id in ["web", "db"] {
+        stop_and_wait(&mut manager, id);
+    }
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use common::{free_port, stop_and_wait, TestDefinition};
+use kojibox_lib::models::{HealthCheck, PortDef, RestartPolicy, ServiceDefinition};
+use kojibox_lib::runtime::RuntimeManager;
+use kojibox_lib::service_manager::ServiceManager;
+use std::path::Path;
+use std::time::Duration;
+
+fn dummy(bin: &str, port: u16) -> ServiceDefinition {
+    ServiceDefinition {
+        binary: bin.to_string(),
+        args: vec![port.to_string()],
+        ports: vec![PortDef {
+            name: "main".to_string(),
+            port,
+            protocol: "tcp".to_string(),
+        }],
+        health_check: HealthCheck {
+            kind: "port".to_string(),
+            target: format!("127.0.0.1:{port}"),
+            timeout_ms: 1000,
+            interval_ms: 200,
+            http: None,
+            command: Vec::new(),
+        },
+        restart_policy: RestartPolicy {
+            max_retries: 0,
+            backoff_ms: 100,
+            max_backoff_ms: 60_000,
+            window_ms: 300_000,
+        },
+        ..ServiceDefinition::test("dummy")
+    }
+}
+
+fn manager(def: ServiceDefinition, root: &Path) -> ServiceManager {
+    let mut manager = ServiceManager::new(RuntimeManager::new("."), vec![def], root.join("logs"));
+    manager.set_state_dir(root.join("state"));
+    manager
+}
+
+#[test]
+fn reattach_adopts_service_left_running_by_previous_session() {
+    let bin = std::env::var("CARGO_BIN_EXE_dummy_service").expect("dummy service bin");
+    let port = free_port();
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+
+    let mut first = manager(dummy(&bin, port), temp_dir.path());
+    first.start("dummy").expect("start dummy");
+    let running = first.wait_for("dummy", Duration::from_secs(10)).expect("state");
+    assert_eq!(running.state, "running");
+    assert!(temp_dir.path().join("state/pids/dummy.json").exists());
+    // Simulate an app crash: the manager goes away, the process does not.
+    drop(first);
+
+    let mut second = manager(dummy(&bin, port), temp_dir.path());
+    let adopted = second.reattach();
+    assert_eq!(adopted.len(), 1);
+    assert_eq!(adopted[0].state, "running");
+    assert_eq!(adopted[0].pid, running.pid);
+
+    let again = second.start("dummy").expect("start adopted");
+    assert_eq!(again.pid, running.pid);
+
+    let stopped = stop_and_wait(&mut second, "dummy");
+    assert_eq!(stopped.state, "stopped");
+    assert!(!temp_dir.path().join("state/pids/dummy.json").exists());
+}
+
+#[test]
+fn reattach_discards_stale_pidfiles() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let pids = temp_dir.path().join("state/pids");
+    std::fs::create_dir_all(&pids).expect("pids dir");
+    std::fs::write(
+        pids.join("dummy.json"),
+        r#"{"pid": 4194000, "startedAt": 1, "binary": "dummy_service", "ports": [1]}"#,
+    )
+    .expect("write pidfile");
+
+    let mut manager = manager(dummy("dummy_service", free_port()), temp_dir.path());
+    assert!(manager.reattach().is_empty());
+    assert!(!pids.join("dummy.json").exists());
+}
+
+// Numbers of the "line N" messages in the manager's buffer, in order.
+fn line_numbers(manager: &ServiceManager) -> Vec<u64> {
+    manager
+        .logs("chatty", 1_000)
+        .into_iter()
+        .filter_map(|entry| entry.message.strip_prefix("line ")?.parse().ok())
+        .collect()
+}
+
+#[cfg(unix)]
+#[test]
+fn reattach_keeps_logging_output_of_adopted_services() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    // Adoption compares executable names, and /bin/sh is often a symlink.
+    let shell = std::fs::canonicalize("/bin/sh").expect("shell");
+    let def = ServiceDefinition {
+        binary: shell.display().to_string(),
+        health_check: HealthCheck {
+            kind: "pid".to_string(),
+            interval_ms: 100,
+            ..HealthCheck::default()
+        },
+        ..ServiceDefinition::shell(
+            "chatty",
+            "i=0; while true; do i=$((i+1)); echo \"line $i\"; sleep 0.05; done",
+        )
+    };
+
+    let mut first = manager(def.clone(), temp_dir.path());
+    first.start("chatty").expect("start chatty");
+    let running = first
+        .wait_for("chatty", Duration::from_secs(10))
+        .expect("state");
+    assert_eq!(running.state, "running");
+    std::thread::sleep(Duration::from_millis(300));
+    let seen = line_numbers(&first).last().copied().expect("lines before exit");
+    drop(first);
+    // The service keeps writing while no app is around to read its output.
+    std::thread::sleep(Duration::from_secs(1));
+
+    let mut second = manager(def, temp_dir.path());
+    let adopted = second.reattach();
+    assert_eq!(adopted.len(), 1);
+    assert_eq!(adopted[0].pid, running.pid);
+    std::thread::sleep(Duration::from_millis(500));
+    let lines = line_numbers(&second);
+    assert!(!lines.is_empty(), "no output captured after reattach");
+    // Picked up where the first session stopped, not where the file ends.
+    assert!(lines[0] <= seen + 5, "resumed at line {} after {seen}", lines[0]);
+    assert!(lines.windows(2).all(|pair| pair[1] == pair[0] + 1), "{lines:?}");
+
+    stop_and_wait(&mut second, "chatty");
+}
@@ -0,0 +1,154 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{free_port, stop_and_wait, TestDefinition};
+use kojibox_lib::config_store::ServiceConfig;
+use kojibox_lib::models::{PortDef, ReloadPolicy, ServiceDefinition};
+use kojibox_lib::runtime::RuntimeManager;
+use kojibox_lib::service_manager::ServiceManager;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+fn reloadable_service(marker: &Path, port: u16) -> ServiceDefinition {
+    ServiceDefinition {
+        args: vec![
+            "-c".to_string(),
+            format!(
+                "trap 'echo reloaded >> {}' HUP; while true; do sleep 0.05; done",
+                marker.display()
+            ),
+            "reloady".to_string(),
+            "${port.main}".to_string(),
+        ],
+        ports: vec![PortDef {
+            name: "main".to_string(),
+            port,
+            protocol: "tcp".to_string(),
+        }],
+        reload_policy: Some(ReloadPolicy {
+            signal: Some("hup".to_string()),
+            ..ReloadPolicy::default()
+        }),
+        ..ServiceDefinition::test("reloady")
+    }
+}
+
+fn config_with_port(port: u16) -> ServiceConfig {
+    ServiceConfig {
+        id: "reloady".to_string(),
+        ports: HashMap::from([("main".to_string(), port)]),
+        ..ServiceConfig::default()
+    }
+}
+
+fn wait_for_file(path: &Path) -> String {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let content = fs::read_to_string(path).unwrap_or_default();
+        if !content.is_empty() || Instant::now() > deadline {
+            return content;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn reload_signals_in_place_and_reports_changes_that_need_a_restart() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let root = temp_dir.path();
+    let marker = root.join("reloads.txt");
+    let port = free_port();
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new(root),
+        vec![reloadable_service(&marker, port)],
+        root.join("logs"),
+    );
+    let config_dir = root.join("runtime").join("config").join("reloady");
+    fs::create_dir_all(&config_dir).expect("config dir");
+    fs::write(config_dir.join("app.conf.tmpl"), "listen=${port.main}\n").expect("template");
+
+    manager
+        .start_with_config("reloady", config_with_port(port))
+        .expect("start");
+    let state = manager
+        .wait_for("reloady", Duration::from_secs(5))
+        .expect("state");
+    assert_eq!(state.state, "running");
+    let pid = state.pid;
+
+    // An edited template is re-rendered and picked up by the reload signal.
+    fs::write(
+        config_dir.join("app.conf.tmpl"),
+        "listen=${port.main}\nworkers=4\n",
+    )
+    .expect("template");
+    let outcome = manager
+        .apply_config_no_restart("reloady", config_with_port(port))
+        .expect("reload");
+    assert_eq!(outcome.outcome, "reloaded");
+    assert_eq!(outcome.method.as_deref(), Some("signal"));
+    assert!(outcome.rendered_files[0].ends_with("app.conf"));
+    assert_eq!(
+        fs::read_to_string(config_dir.join("app.conf")).expect("rendered"),
+        format!("listen={port}\nworkers=4\n")
+    );
+    assert_eq!(wait_for_file(&marker).trim(), "reloaded");
+    assert_eq!(manager.state("reloady").expect("state").pid, pid);
+
+    // A new port only reaches the process through a restart.
+    let new_port = free_port();
+    let outcome = manager
+        .apply_config_no_restart("reloady", config_with_port(new_port))
+        .expect("reload");
+    assert_eq!(outcome.outcome, "restart-required");
+    assert_eq!(
+        outcome.reasons,
+        vec![
+            format!("port main changed: {port} -> {new_port}"),
+            "args changed".to_string(),
+        ]
+    );
+    // Nothing is written until the change is applied.
+    assert!(outcome.rendered_files[0].ends_with("app.conf"));
+    assert_eq!(
+        fs::read_to_string(config_dir.join("app.conf")).expect("rendered"),
+        format!("listen={port}\nworkers=4\n")
+    );
+    assert_eq!(manager.state("reloady").expect("state").pid, pid);
+
+    let outcome = manager
+        .reload_with_config("reloady", config_with_port(new_port), true)
+        .expect("restart");
+    assert_eq!(outcome.outcome, "restarted");
+    let state = manager
+        .wait_for("reloady", Duration::from_secs(5))
+        .expect("state");
+    assert_eq!(state.state, "running");
+    assert_ne!(state.pid, pid);
+    assert_eq!(
+        fs::read_to_string(config_dir.join("app.conf")).expect("rendered"),
+        format!("listen={new_port}\nworkers=4\n")
+    );
+    stop_and_wait(&mut manager, "reloady");
+}
+
+#[test]
+fn reload_of_a_stopped_service_applies_on_next_start() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let root = temp_dir.path();
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new(root),
+        vec![reloadable_service(&root.join("reloads.txt"), free_port())],
+        root.join("logs"),
+    );
+    let outcome = manager
+        .apply_config_no_restart("reloady", config_with_port(free_port()))
+        .expect("reload");
+    assert_eq!(outcome.outcome, "not-running");
+    assert!(manager
+        .apply_config_no_restart("missing", ServiceConfig::default())
+        .is_err());
+}
@@ -0,0 +1,135 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{stop_and_wait, TestDefinition};
+use kojibox_lib::models::{HealthCheck, RestartPolicy, RunRecord, ServiceDefinition};
+use kojibox_lib::runtime::RuntimeManager;
+use kojibox_lib::service_manager::ServiceManager;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+// Crashes on its first run, then keeps running.
+fn flaky_service(marker: &Path) -> ServiceDefinition {
+    let script = format!(
+        "if [ -e {marker} ]; then while true; do sleep 0.05; done; fi; \
+         touch {marker}; echo boom >&2; sleep 0.3; exit 3",
+        marker = marker.display()
+    );
+    ServiceDefinition {
+        args: vec!["-c".to_string(), script],
+        health_check: HealthCheck {
+            interval_ms: 50,
+            ..HealthCheck::default()
+        },
+        restart_policy: RestartPolicy {
+            max_retries: 5,
+            backoff_ms: 100,
+            max_backoff_ms: 400,
+            window_ms: 60_000,
+        },
+        ..ServiceDefinition::test("flaky")
+    }
+}
+
+fn manager(root: &Path) -> ServiceManager {
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new("."),
+        vec![flaky_service(&root.join("marker"))],
+        root.join("logs"),
+    );
+    manager.set_state_dir(root.join("state"));
+    manager
+}
+
+// Ticks until the newest run matches `done`.
+fn wait_for_run(
+    manager: &mut ServiceManager,
+    done: impl Fn(&[RunRecord]) -> bool,
+) -> Vec<RunRecord> {
+    let deadline = Instant::now() + Duration::from_secs(15);
+    loop {
+        manager.tick();
+        let runs = manager.runs("flaky", 10);
+        if done(&runs) || Instant::now() > deadline {
+            return runs;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+fn kill(pid: u32) {
+    Command::new("kill")
+        .args(["-9", &pid.to_string()])
+        .status()
+        .expect("kill");
+}
+
+#[test]
+fn runs_record_crashes_signals_restart_reasons_and_stops() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let mut manager = manager(temp_dir.path());
+    manager.start("flaky").expect("start");
+
+    let runs = wait_for_run(&mut manager, |runs| runs.len() == 2);
+    assert_eq!(runs.len(), 2);
+    let crashed = &runs[1];
+    assert_eq!(crashed.run, 1);
+    assert_eq!(crashed.outcome, "crashed");
+    assert_eq!(crashed.exit_code, Some(3));
+    assert_eq!(crashed.signal, None);
+    assert_eq!(crashed.restart_reason, None);
+    assert!(crashed.stopped_at.is_some());
+    assert!(crashed.uptime_secs.is_some());
+    assert!(crashed.stderr_tail.iter().any(|line| line == "boom"));
+    let restarted = &runs[0];
+    assert_eq!(restarted.outcome, "running");
+    assert_eq!(
+        restarted.restart_reason.as_deref(),
+        Some("crash: process exited with code 3 (attempt 1)")
+    );
+
+    kill(restarted.pid.expect("pid"));
+    let runs = wait_for_run(&mut manager, |runs| runs.len() == 3);
+    let killed = manager.run("flaky", 2).expect("run 2");
+    assert_eq!(killed.outcome, "crashed");
+    assert_eq!(killed.exit_code, None);
+    assert_eq!(killed.signal, Some(9));
+    assert_eq!(
+        runs[0].restart_reason.as_deref(),
+        Some("crash: process killed by signal 9 (attempt 2)")
+    );
+
+    manager.wait_for("flaky", Duration::from_secs(10));
+    stop_and_wait(&mut manager, "flaky");
+    let stopped = manager.run("flaky", 3).expect("run 3");
+    assert_eq!(stopped.outcome, "stopped");
+    assert!(stopped.stop_method.is_some());
+    assert!(stopped.stderr_tail.is_empty());
+    assert_eq!(manager.runs("flaky", 1).len(), 1);
+    assert!(manager.run("flaky", 4).is_err());
+}
+
+#[test]
+fn runs_left_open_by_a_previous_session_are_marked_lost() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    std::fs::write(temp_dir.path().join("marker"), "").expect("marker");
+    let mut first = manager(temp_dir.path());
+    first.start("flaky").expect("start");
+    let runs = wait_for_run(&mut first, |runs| !runs.is_empty());
+    // The app goes away and the service dies before it comes back.
+    drop(first);
+    kill(runs[0].pid.expect("pid"));
+    std::thread::sleep(Duration::from_millis(200));
+
+    let mut second = manager(temp_dir.path());
+    assert!(second.reattach().is_empty());
+    let lost = second.run("flaky", 1).expect("run 1");
+    assert_eq!(lost.outcome, "lost");
+    assert!(lost.stopped_at.is_some());
+    assert_eq!(
+        lost.error.as_deref(),
+        Some("exited while the app was not running")
+    );
+}
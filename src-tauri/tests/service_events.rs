@@ -0,0 +1,71 @@
+mod common;
+
+use common::{free_port, stop_and_wait, TestDefinition};
+use kojibox_lib::events::ServiceEvent;
+use kojibox_lib::models::{HealthCheck, PortDef, RestartPolicy, ServiceDefinition};
+use kojibox_lib::runtime::RuntimeManager;
+use kojibox_lib::service_manager::ServiceManager;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[test]
+fn start_and_stop_emit_state_health_and_log_events() {
+    let bin = std::env::var("CARGO_BIN_EXE_dummy_service").expect("dummy service bin");
+    let port = free_port();
+    let def = ServiceDefinition {
+        binary: bin,
+        args: vec![port.to_string()],
+        ports: vec![PortDef {
+            name: "http".to_string(),
+            port,
+            protocol: "tcp".to_string(),
+        }],
+        health_check: HealthCheck {
+            kind: "port".to_string(),
+            target: format!("127.0.0.1:{port}"),
+            timeout_ms: 2000,
+            interval_ms: 200,
+            http: None,
+            command: Vec::new(),
+        },
+        restart_policy: RestartPolicy {
+            max_retries: 0,
+            backoff_ms: 100,
+            max_backoff_ms: 60_000,
+            window_ms: 300_000,
+        },
+        ..ServiceDefinition::test("dummy")
+    };
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let runtime = RuntimeManager::new(".");
+    let mut manager = ServiceManager::new(runtime, vec![def], PathBuf::from(temp_dir.path()));
+    let events = manager.subscribe();
+
+    manager.start("dummy").expect("start dummy");
+    let state = manager.wait_for("dummy", Duration::from_secs(10)).expect("state");
+    assert_eq!(state.state, "running");
+    stop_and_wait(&mut manager, "dummy");
+
+    let received: Vec<ServiceEvent> = events.try_iter().collect();
+    let transitions: Vec<String> = received
+        .iter()
+        .filter_map(|event| match event {
+            ServiceEvent::StateChanged { state, .. } => Some(state.state.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(transitions.first().map(String::as_str), Some("starting"));
+    assert!(transitions.contains(&"running".to_string()));
+    assert_eq!(transitions.last().map(String::as_str), Some("stopped"));
+    assert!(received
+        .iter()
+        .any(|event| matches!(event, ServiceEvent::Health { healthy: true, .. })));
+    assert!(received.iter().any(|event| matches!(
+        event,
+        ServiceEvent::LogLine { entry } if entry.message == "service started"
+    )));
+
+    let json = serde_json::to_value(&received[0]).expect("serialize event");
+    assert_eq!(json["kind"], "stateChanged");
+    assert_eq!(received[0].name(), "service:stateChanged");
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::{free_port, stop_and_wait, TestDefinition};
+use kojibox_lib::metrics;
+use kojibox_lib::models::{HealthCheck, PortDef, RestartPolicy, ServiceDefinition};
+use kojibox_lib::runtime::RuntimeManager;
+use kojibox_lib::service_manager::ServiceManager;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[test]
+fn service_metrics_cover_tracked_process_and_its_listening_port() {
+    let bin = std::env::var("CARGO_BIN_EXE_dummy_service").expect("dummy service bin");
+    let port = free_port();
+    let def = ServiceDefinition {
+        binary: bin,
+        args: vec![port.to_string()],
+        ports: vec![PortDef {
+            name: "main".to_string(),
+            port,
+            protocol: "tcp".to_string(),
+        }],
+        health_check: HealthCheck {
+            kind: "port".to_string(),
+            target: format!("127.0.0.1:{port}"),
+            timeout_ms: 1000,
+            interval_ms: 200,
+            http: None,
+            command: Vec::new(),
+        },
+        restart_policy: RestartPolicy {
+            max_retries: 0,
+            backoff_ms: 100,
+            max_backoff_ms: 60_000,
+            window_ms: 300_000,
+        },
+        ..ServiceDefinition::test("dummy")
+    };
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let runtime = RuntimeManager::new(".");
+    let mut manager = ServiceManager::new(runtime, vec![def], PathBuf::from(temp_dir.path()));
+    manager.start("dummy").expect("start dummy");
+    let state = manager.wait_for("dummy", Duration::from_secs(10)).expect("state");
+    assert_eq!(state.state, "running");
+
+    let roots = manager.tracked_pids();
+    assert_eq!(roots, vec![("dummy".to_string(), state.pid.expect("pid"))]);
+    let snapshot = metrics::service_metrics(&roots);
+    assert_eq!(snapshot.len(), 1);
+    assert_eq!(snapshot[0].service, "dummy");
+    assert!(snapshot[0].pids.contains(&state.pid.unwrap()));
+    assert!(snapshot[0].rss_bytes > 0);
+    if cfg!(any(target_os = "linux", target_os = "windows")) {
+        assert_eq!(snapshot[0].listening_ports, vec![port]);
+    }
+
+    stop_and_wait(&mut manager, "dummy");
+}
@@ -0,0 +1,117 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{stop_and_wait, TestDefinition};
+use kojibox_lib::models::{Hook, LifecycleHooks, ServiceDefinition, StopPolicy};
+use kojibox_lib::runtime::RuntimeManager;
+use kojibox_lib::service_manager::ServiceManager;
+use std::time::{Duration, Instant};
+
+#[test]
+fn stop_command_with_a_lot_of_stderr_does_not_block_until_the_timeout() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    // Far more than a pipe buffer holds.
+    let script = "head -c 200000 /dev/zero | tr '\\0' x >&2; exit 1";
+    let def = ServiceDefinition {
+        stop_policy: StopPolicy {
+            command: vec!["/bin/sh".to_string(), "-c".to_string(), script.to_string()],
+            timeout_ms: 5_000,
+            ..StopPolicy::default()
+        },
+        ..ServiceDefinition::shell("noisy", "while true; do sleep 0.05; done")
+    };
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new("."),
+        vec![def],
+        temp_dir.path().join("logs"),
+    );
+    manager.start("noisy").expect("start");
+    let state = manager
+        .wait_for("noisy", Duration::from_secs(10))
+        .expect("state");
+    assert_eq!(state.state, "running");
+
+    let started = Instant::now();
+    let stopped = stop_and_wait(&mut manager, "noisy");
+    assert!(started.elapsed() < Duration::from_secs(4));
+    assert_eq!(stopped.stop_method.as_deref(), Some("signal"));
+    let failure = manager
+        .logs("noisy", 50)
+        .into_iter()
+        .find(|entry| entry.message.starts_with("stop command failed: "))
+        .expect("stop command failure logged");
+    assert!(failure.message.ends_with("xxx"));
+}
+
+#[test]
+fn stop_returns_while_hooks_run_and_a_new_start_waits_for_it() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let def = ServiceDefinition {
+        hooks: LifecycleHooks {
+            pre_stop: vec![Hook {
+                command: vec!["sleep".to_string(), "1".to_string()],
+                timeout_ms: 5_000,
+                on_failure: "warn".to_string(),
+            }],
+            ..LifecycleHooks::default()
+        },
+        ..ServiceDefinition::shell("slow", "while true; do sleep 0.05; done")
+    };
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new("."),
+        vec![def],
+        temp_dir.path().join("logs"),
+    );
+    manager.start("slow").expect("start");
+    let first = manager
+        .wait_for("slow", Duration::from_secs(10))
+        .expect("state");
+    assert_eq!(first.state, "running");
+
+    let started = Instant::now();
+    let stopping = manager.stop("slow").expect("stop");
+    assert!(started.elapsed() < Duration::from_millis(500));
+    assert_eq!(stopping.state, "stopping");
+    assert_eq!(stopping.pid, first.pid);
+
+    // Starting again while the stop runs gets a new process once it is done.
+    manager.start("slow").expect("start again");
+    let second = manager
+        .wait_for("slow", Duration::from_secs(10))
+        .expect("state");
+    assert_eq!(second.state, "running");
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    assert_ne!(second.pid, first.pid);
+    let second = stop_and_wait(&mut manager, "slow");
+    assert_eq!(second.state, "stopped");
+}
+
+#[test]
+fn stop_signal_goes_to_the_root_process_only() {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let marker = temp_dir.path().join("worker-got-term");
+    // Like a postmaster: the root handles the signal, its worker must not
+    // see it before the root decides what to do.
+    let script = format!(
+        "(trap 'echo term > {marker}; exit 0' TERM; while true; do sleep 0.05; done) & \
+         trap 'exit 0' TERM; while true; do sleep 0.05; done",
+        marker = marker.display()
+    );
+    let mut manager = ServiceManager::new(
+        RuntimeManager::new("."),
+        vec![ServiceDefinition::shell("master", &script)],
+        temp_dir.path().join("logs"),
+    );
+    manager.start("master").expect("start");
+    let state = manager
+        .wait_for("master", Duration::from_secs(10))
+        .expect("state");
+    assert_eq!(state.state, "running");
+
+    let stopped = stop_and_wait(&mut manager, "master");
+    assert_eq!(stopped.stop_method.as_deref(), Some("signal"));
+    std::thread::sleep(Duration::from_millis(200));
+    assert!(!marker.exists(), "worker received the stop signal");
+    assert!(manager.leaked_processes().is_empty());
+}
@@ -4,15 +4,151 @@ export type ServiceState = {
   pid: number | null;
   lastError: string | null;
   lastUpdated: string;
+  stopMethod: string | null;
+  blockedBy: string | null;
+  lastExitCode?: number | null;
+  lastSignal?: number | null;
+  stderrTail?: string[];
+};
+
+export type RunRecord = {
+  service: string;
+  run: number;
+  pid: number | null;
+  startedAt: string;
+  stoppedAt: string | null;
+  uptimeSecs: number | null;
+  outcome: "running" | "stopped" | "exited" | "crashed" | "failed" | "lost";
+  exitCode: number | null;
+  signal: number | null;
+  restartReason: string | null;
+  stopMethod: string | null;
+  error: string | null;
+  stderrTail: string[];
+};
+
+export type LeakedProcess = {
+  service: string;
+  pid: number;
+  name: string;
+  startedAt: number;
+};
+
+export type ServiceDefinition = {
+  id: string;
+  name: string;
+  binary: string;
+  args: string[];
+  env: Record<string, string>;
+  cwd: string;
+  ports: { name: string; port: number; protocol: string }[];
+  dependsOn: string[];
+  healthCheck: { type: string; target: string; timeoutMs: number; intervalMs: number; command?: string[] };
+  logFormat: string | null;
+  version?: string | null;
+  reloadPolicy?: ReloadPolicy | null;
+  hooks?: LifecycleHooks;
+};
+
+export type Hook = {
+  command: string[];
+  timeoutMs: number;
+  onFailure: "abort" | "warn";
+};
+
+export type LifecycleHooks = {
+  preStart: Hook[];
+  postStart: Hook[];
+  preStop: Hook[];
+  postStop: Hook[];
+};
+
+export type ReloadPolicy = {
+  signal: string | null;
+  command: string[];
+  timeoutMs: number;
+};
+
+export type ReloadOutcome = {
+  service: string;
+  outcome: "reloaded" | "restarted" | "restart-required" | "not-running";
+  method: string | null;
+  reasons: string[];
+  renderedFiles: string[];
+  state: ServiceState;
+};
+
+export type HealthProbe = {
+  ts: number;
+  latencyMs: number;
+  healthy: boolean;
+  error: string | null;
+};
+
+export type HealthStats = {
+  windowSecs: number;
+  probes: number;
+  uptimePercent: number | null;
+  meanLatencyMs: number | null;
+  flaps: number;
+};
+
+export type HealthReport = {
+  service: string;
+  probes: HealthProbe[];
+  windows: HealthStats[];
+};
+
+export type LaunchPlan = {
+  service: string;
+  binary: string;
+  binaryFound: boolean;
+  args: string[];
+  cwd: string;
+  env: Record<string, string>;
+  ports: { name: string; port: number; protocol: string }[];
+  healthCheck: ServiceDefinition["healthCheck"];
+  dependsOn: string[];
+  generatedFiles: string[];
+  warnings: string[];
 };
 
 export type LogEntry = {
   ts: string;
+  seq?: number;
   level: string;
   message: string;
   service?: string;
 };
 
+export type LogSnapshot = {
+  entries: LogEntry[];
+  lastSeq: number;
+};
+
+export type LogQuery = {
+  services: string[];
+  text?: string;
+  regex?: boolean;
+  from?: string;
+  to?: string;
+  minLevel?: string;
+  cursor?: string;
+  limit?: number;
+};
+
+export type LogPage = {
+  entries: LogEntry[];
+  nextCursor: string | null;
+};
+
+export type ServiceEvent =
+  | { kind: "stateChanged"; previous: string | null; state: ServiceState }
+  | { kind: "crashed"; service: string; exitCode: number | null }
+  | { kind: "restarted"; service: string; attempt: number }
+  | { kind: "health"; service: string; healthy: boolean; error: string | null }
+  | { kind: "logLine"; entry: LogEntry };
+
 export type InstallerStatus = {
   phase: string;
   progress: number;
@@ -26,6 +162,17 @@ export type MetricsSnapshot = {
   memMB: number;
 };
 
+export type ServiceMetrics = {
+  service: string;
+  pids: number[];
+  cpuPercent: number;
+  rssBytes: number;
+  diskReadBytes: number;
+  diskWriteBytes: number;
+  threads: number | null;
+  listeningPorts: number[];
+};
+
 export type AppConfig = {
   schemaVersion: number;
   installPath: string;
@@ -33,6 +180,8 @@ export type AppConfig = {
   telemetryOptIn: boolean;
   updateFeedUrl: string;
   updatePublicKeys: string[];
+  logDiskBudgetMb: number;
+  activeProfile?: string | null;
 };
 
 export type ProjectConfig = {
@@ -52,6 +201,27 @@ export type ServiceConfig = {
   ports: Record<string, number>;
   env: Record<string, string>;
   args: string[];
+  logRotation: LogRotation;
+};
+
+export type ProfileService = {
+  id: string;
+  version?: string | null;
+  ports?: Record<string, number>;
+  env?: Record<string, string>;
+};
+
+export type Profile = {
+  schemaVersion: number;
+  name: string;
+  services: ProfileService[];
+};
+
+export type LogRotation = {
+  maxSizeBytes: number;
+  maxFiles: number;
+  maxAgeDays: number;
+  compress: boolean;
 };
 
 export type DomainMapping = {